@@ -0,0 +1,210 @@
+//! Pipeline throughput.
+//!
+//! The `pipeline` group builds pipelines, and runs them with and without
+//! freezing them first, only the building differs between the storages.
+//! Compare the storages by running with and without the `handle-smallvec` feature:
+//!
+//! ```sh
+//! cargo bench --bench pipeline
+//! cargo bench --bench pipeline --features handle-smallvec
+//! ```
+//!
+//! The `hlist` group compares a statically dispatched `hpipeline!` with the
+//! same handlers awaited one after another, and the `tuple` group compares
+//! tuple pipelines with a `Vec` of `ArcHandle`s.
+
+use criterion::{
+    criterion_group, criterion_main, measurement::WallTime, BenchmarkGroup, BenchmarkId, Criterion,
+};
+use futures::executor::block_on;
+use handle::{hpipeline, ArcHandle, BoxFuture, ContextExt, Handle, Next, Pipeline};
+
+#[derive(Default)]
+struct Context {
+    index: usize,
+    next: Next<Self, ()>,
+}
+
+impl ContextExt<()> for Context {
+    fn next_mut(&mut self) -> &mut Next<Self, ()> {
+        &mut self.next
+    }
+
+    fn next_ref(&self) -> &Next<Self, ()> {
+        &self.next
+    }
+}
+
+async fn step(cx: &mut Context) {
+    cx.index += 1;
+    cx.next().await
+}
+
+/// A context driving the handlers by popping them off its own `Vec`.
+#[derive(Default)]
+struct PopContext {
+    index: usize,
+    middleware: Vec<ArcHandle<PopContext, ()>>,
+}
+
+impl PopContext {
+    fn next(&mut self) -> BoxFuture<'_, ()> {
+        match self.middleware.pop() {
+            Some(h) => Box::pin(async move { h.call(self).await }),
+            None => Box::pin(async {}),
+        }
+    }
+}
+
+async fn pop_step(cx: &mut PopContext) {
+    cx.index += 1;
+    cx.next().await
+}
+
+async fn checked_step(cx: &mut Context) -> Result<(), ()> {
+    cx.index += 1;
+    Ok(())
+}
+
+fn pipeline(c: &mut Criterion) {
+    let mut group = c.benchmark_group("pipeline");
+
+    for size in [2, 8, 16] {
+        let mut pipeline = Pipeline::new();
+        for _ in 0..size {
+            pipeline.push(step);
+        }
+        group.bench_with_input(BenchmarkId::new("build", size), &size, |b, &size| {
+            b.iter(|| {
+                let mut pipeline = Pipeline::new();
+                for _ in 0..size {
+                    pipeline.push(step);
+                }
+                pipeline.len()
+            })
+        });
+
+        group.bench_function(BenchmarkId::new("run", size), |b| {
+            b.iter(|| {
+                let mut cx = Context::default();
+                block_on(pipeline.run(&mut cx));
+                cx.index
+            })
+        });
+
+        let stack = pipeline.freeze();
+        group.bench_function(BenchmarkId::new("run_frozen", size), |b| {
+            b.iter(|| {
+                let mut cx = Context::default();
+                block_on(stack.run(&mut cx));
+                cx.index
+            })
+        });
+    }
+
+    group.finish();
+}
+
+fn reuse(c: &mut Criterion) {
+    let mut group = c.benchmark_group("reuse");
+    let size = 20;
+
+    let middleware: Vec<ArcHandle<PopContext, ()>> = (0..size)
+        .map(|_| std::sync::Arc::new(pop_step) as ArcHandle<PopContext, ()>)
+        .collect();
+    group.bench_function("clone_per_request", |b| {
+        b.iter(|| {
+            let mut cx = PopContext {
+                index: 0,
+                middleware: middleware.clone(),
+            };
+            block_on(cx.next());
+            cx.index
+        })
+    });
+
+    let mut pipeline = Pipeline::new();
+    for _ in 0..size {
+        pipeline.push(step);
+    }
+    let stack = pipeline.freeze();
+    group.bench_function("snapshot", |b| {
+        b.iter(|| {
+            let mut cx = Context::default();
+            block_on(stack.run(&mut cx));
+            cx.index
+        })
+    });
+
+    group.finish();
+}
+
+fn hlist(c: &mut Criterion) {
+    let mut group = c.benchmark_group("hlist");
+
+    let h = hpipeline!(checked_step, checked_step, checked_step);
+    group.bench_function("hpipeline", |b| {
+        b.iter(|| {
+            let mut cx = Context::default();
+            let _ = block_on(h.call(&mut cx));
+            cx.index
+        })
+    });
+
+    group.bench_function("sequential", |b| {
+        b.iter(|| {
+            let mut cx = Context::default();
+            let _ = block_on(async {
+                checked_step(&mut cx).await?;
+                checked_step(&mut cx).await?;
+                checked_step(&mut cx).await
+            });
+            cx.index
+        })
+    });
+
+    group.finish();
+}
+
+fn tuple(c: &mut Criterion) {
+    let mut group = c.benchmark_group("tuple");
+
+    fn bench<H>(group: &mut BenchmarkGroup<'_, WallTime>, size: usize, h: H)
+    where
+        H: for<'a> Handle<'a, Context, Output = Result<(), ()>>,
+    {
+        group.bench_function(BenchmarkId::new("tuple", size), |b| {
+            b.iter(|| {
+                let mut cx = Context::default();
+                let _ = block_on(h.call(&mut cx));
+                cx.index
+            })
+        });
+
+        let handlers: Vec<ArcHandle<Context, Result<(), ()>>> = (0..size)
+            .map(|_| std::sync::Arc::new(checked_step) as ArcHandle<Context, Result<(), ()>>)
+            .collect();
+        group.bench_function(BenchmarkId::new("dyn", size), |b| {
+            b.iter(|| {
+                let mut cx = Context::default();
+                let _ = block_on(async {
+                    for h in &handlers {
+                        h.call(&mut cx).await?;
+                    }
+                    Ok::<_, ()>(())
+                });
+                cx.index
+            })
+        });
+    }
+
+    let s = checked_step;
+    bench(&mut group, 3, (s, s, s));
+    bench(&mut group, 6, (s, s, s, s, s, s));
+    bench(&mut group, 9, (s, s, s, s, s, s, s, s, s));
+
+    group.finish();
+}
+
+criterion_group!(benches, pipeline, reuse, hlist, tuple);
+criterion_main!(benches);
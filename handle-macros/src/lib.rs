@@ -0,0 +1,336 @@
+//! Procedural macros for the [`handle`](https://docs.rs/handle) crate.
+//!
+//! They are re-exported by `handle` behind the `macros` feature, use them from
+//! there.
+
+#![forbid(unsafe_code, rust_2018_idioms)]
+#![deny(nonstandard_style)]
+#![warn(missing_docs, unreachable_pub)]
+
+use proc_macro::TokenStream;
+use proc_macro2::{Ident, TokenStream as TokenStream2, TokenTree};
+use quote::quote;
+use syn::{
+    bracketed, parse::Parse, parse_macro_input, parse_quote, spanned::Spanned, Data, DeriveInput,
+    Error, Fields, FnArg, GenericParam, ImplItem, ItemImpl, Member, Pat, Result, ReturnType, Token,
+    Type,
+};
+
+const EXPECTED: &str = "expected `async fn call(&self, cx: &mut Context) -> Output`";
+
+/// Implements `Handle` from an inherent `impl` block holding a single
+/// `async fn call(&self, cx: &mut Context) -> Output`.
+///
+/// The generics and the where clause of the block are kept, so the handler
+/// may have fields and be generic over its context.
+///
+/// ```ignore
+/// struct Counter {
+///     step: usize,
+/// }
+///
+/// #[handle::handler]
+/// impl Counter {
+///     async fn call(&self, cx: &mut Context) -> usize {
+///         cx.index += self.step;
+///         cx.index
+///     }
+/// }
+/// ```
+#[proc_macro_attribute]
+pub fn handler(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let attr = TokenStream2::from(attr);
+    if !attr.is_empty() {
+        return Error::new(attr.span(), "`#[handler]` takes no arguments")
+            .into_compile_error()
+            .into();
+    }
+
+    let item = parse_macro_input!(item as ItemImpl);
+    expand_handler(item)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+/// Implements `Handle` by delegating to a field of the struct.
+///
+/// The field is the one marked with `#[handle(delegate)]`, or the first field
+/// of a tuple struct when none is marked. The `Context` and the `Output` are those
+/// of the field's handler.
+///
+/// ```ignore
+/// #[derive(handle::Handle)]
+/// struct Logged<H>(H);
+///
+/// #[derive(handle::Handle)]
+/// struct Metered<H> {
+///     #[handle(delegate)]
+///     inner: H,
+///     label: &'static str,
+/// }
+/// ```
+#[proc_macro_derive(Handle, attributes(handle))]
+pub fn derive_handle(item: TokenStream) -> TokenStream {
+    let item = parse_macro_input!(item as DeriveInput);
+    expand_derive(item)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+/// Implements `HandlerMeta` from the types listed in the `handler` attribute,
+/// the handler is named by its identifier.
+///
+/// The listed types cannot use the generic parameters of the handler, the
+/// derive rejects them.
+///
+/// ```ignore
+/// #[derive(handle::HandlerMeta)]
+/// #[handler(before = [Router], after = [Logger, Tracer])]
+/// struct Auth;
+/// ```
+#[proc_macro_derive(HandlerMeta, attributes(handler))]
+pub fn derive_handler_meta(item: TokenStream) -> TokenStream {
+    let item = parse_macro_input!(item as DeriveInput);
+    expand_meta(item)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+fn expand_meta(item: DeriveInput) -> Result<TokenStream2> {
+    let mut before = Vec::new();
+    let mut after = Vec::new();
+    for attr in item.attrs.iter().filter(|a| a.path().is_ident("handler")) {
+        attr.parse_nested_meta(|meta| {
+            let types = if meta.path.is_ident("before") {
+                &mut before
+            } else if meta.path.is_ident("after") {
+                &mut after
+            } else {
+                return Err(meta.error("expected `before` or `after`"));
+            };
+            let input = meta.value()?;
+            let content;
+            bracketed!(content in input);
+            types.extend(content.parse_terminated(Type::parse, Token![,])?);
+            Ok(())
+        })?;
+    }
+
+    // The lists are consts, which cannot use the generic parameters.
+    let params: Vec<_> = item
+        .generics
+        .params
+        .iter()
+        .filter_map(|param| match param {
+            GenericParam::Type(ty) => Some(&ty.ident),
+            GenericParam::Const(c) => Some(&c.ident),
+            GenericParam::Lifetime(_) => None,
+        })
+        .collect();
+    for ty in before.iter().chain(&after) {
+        if let Some(ident) = find_ident(quote!(#ty), &params) {
+            return Err(Error::new(
+                ident.span(),
+                format!(
+                    "`before` and `after` cannot use the generic parameter `{}` of the handler",
+                    ident
+                ),
+            ));
+        }
+    }
+
+    let mut generics = item.generics.clone();
+    generics
+        .make_where_clause()
+        .predicates
+        .push(parse_quote!(Self: 'static));
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let ident = &item.ident;
+    let name = ident.to_string();
+
+    Ok(quote! {
+        impl #impl_generics ::handle::HandlerMeta for #ident #ty_generics #where_clause {
+            fn name() -> &'static str {
+                #name
+            }
+
+            fn must_run_before() -> &'static [::core::any::TypeId] {
+                const BEFORE: &[::core::any::TypeId] = &[#(::core::any::TypeId::of::<#before>()),*];
+                BEFORE
+            }
+
+            fn must_run_after() -> &'static [::core::any::TypeId] {
+                const AFTER: &[::core::any::TypeId] = &[#(::core::any::TypeId::of::<#after>()),*];
+                AFTER
+            }
+        }
+    })
+}
+
+/// Returns the first of the `idents` found in the `tokens`.
+fn find_ident(tokens: TokenStream2, idents: &[&Ident]) -> Option<Ident> {
+    tokens.into_iter().find_map(|token| match token {
+        TokenTree::Ident(ident) if idents.iter().any(|i| **i == ident) => Some(ident),
+        TokenTree::Group(group) => find_ident(group.stream(), idents),
+        _ => None,
+    })
+}
+
+fn expand_derive(item: DeriveInput) -> Result<TokenStream2> {
+    let fields = match &item.data {
+        Data::Struct(data) => &data.fields,
+        _ => {
+            return Err(Error::new(
+                item.ident.span(),
+                "`#[derive(Handle)]` only supports structs",
+            ))
+        }
+    };
+
+    let mut delegate = None;
+    for (i, field) in fields.iter().enumerate() {
+        for attr in field.attrs.iter().filter(|a| a.path().is_ident("handle")) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("delegate") {
+                    Ok(())
+                } else {
+                    Err(meta.error("expected `delegate`"))
+                }
+            })?;
+            if delegate.is_some() {
+                return Err(Error::new(
+                    attr.span(),
+                    "only one field can be marked with `#[handle(delegate)]`",
+                ));
+            }
+            delegate = Some((i, field));
+        }
+    }
+
+    let (i, field) = match (delegate, fields) {
+        (Some(delegate), _) => delegate,
+        (None, Fields::Unnamed(unnamed)) if !unnamed.unnamed.is_empty() => (0, &unnamed.unnamed[0]),
+        _ => {
+            return Err(Error::new(
+                item.ident.span(),
+                "mark the field to delegate to with `#[handle(delegate)]`",
+            ))
+        }
+    };
+    let member = field
+        .ident
+        .clone()
+        .map_or_else(|| Member::from(i), Member::from);
+    let ty = &field.ty;
+
+    let mut generics = item.generics.clone();
+    generics.params.insert(0, parse_quote!('__handle));
+    generics.params.push(parse_quote!(__HandleContext));
+    let predicates = &mut generics.make_where_clause().predicates;
+    predicates.push(parse_quote!(#ty: ::handle::Handle<'__handle, __HandleContext>));
+    predicates.push(parse_quote!(Self: ::std::marker::Send + ::std::marker::Sync + 'static));
+    let (impl_generics, _, where_clause) = generics.split_for_impl();
+    let (_, ty_generics, _) = item.generics.split_for_impl();
+    let ident = &item.ident;
+
+    Ok(quote! {
+        impl #impl_generics ::handle::Handle<'__handle, __HandleContext> for #ident #ty_generics
+        #where_clause
+        {
+            type Output = <#ty as ::handle::Handle<'__handle, __HandleContext>>::Output;
+
+            #[inline]
+            fn call(
+                &'__handle self,
+                cx: &'__handle mut __HandleContext,
+            ) -> ::handle::BoxFuture<'__handle, Self::Output> {
+                ::handle::Handle::call(&self.#member, cx)
+            }
+        }
+    })
+}
+
+fn expand_handler(item: ItemImpl) -> Result<TokenStream2> {
+    if let Some((_, path, _)) = &item.trait_ {
+        return Err(Error::new(
+            path.span(),
+            "`#[handler]` must be placed on an inherent `impl` block",
+        ));
+    }
+
+    let f = match item.items.as_slice() {
+        [ImplItem::Fn(f)] => f,
+        [_] => return Err(Error::new(item.items[0].span(), EXPECTED)),
+        _ => return Err(Error::new(item.self_ty.span(), EXPECTED)),
+    };
+
+    let sig = &f.sig;
+    if sig.ident != "call" {
+        return Err(Error::new(sig.ident.span(), EXPECTED));
+    }
+    if sig.asyncness.is_none() {
+        return Err(Error::new(
+            sig.fn_token.span(),
+            "`call` must be an `async fn`",
+        ));
+    }
+    if !sig.generics.params.is_empty() || sig.generics.where_clause.is_some() {
+        return Err(Error::new(
+            sig.generics.span(),
+            "`call` cannot have its own generics, declare them on the `impl` block",
+        ));
+    }
+
+    let mut inputs = sig.inputs.iter();
+    match inputs.next() {
+        Some(FnArg::Receiver(r)) if r.reference.is_some() && r.mutability.is_none() => {}
+        Some(arg) => return Err(Error::new(arg.span(), "expected `&self`")),
+        None => return Err(Error::new(sig.inputs.span(), EXPECTED)),
+    }
+    let (pat, cx) = match inputs.next() {
+        Some(FnArg::Typed(arg)) => match (&*arg.pat, &*arg.ty) {
+            (Pat::Ident(_) | Pat::Wild(_), Type::Reference(ty))
+                if ty.mutability.is_some() && ty.lifetime.is_none() =>
+            {
+                (&arg.pat, &ty.elem)
+            }
+            _ => {
+                return Err(Error::new(
+                    arg.span(),
+                    "expected the context as `cx: &mut Context`",
+                ))
+            }
+        },
+        Some(arg) => return Err(Error::new(arg.span(), EXPECTED)),
+        None => return Err(Error::new(sig.inputs.span(), EXPECTED)),
+    };
+    if let Some(arg) = inputs.next() {
+        return Err(Error::new(arg.span(), "unexpected argument"));
+    }
+
+    let output: Type = match &sig.output {
+        ReturnType::Default => parse_quote!(()),
+        ReturnType::Type(_, ty) => (**ty).clone(),
+    };
+
+    let mut generics = item.generics.clone();
+    generics.params.insert(0, parse_quote!('__handle));
+    let (impl_generics, _, where_clause) = generics.split_for_impl();
+    let self_ty = &item.self_ty;
+    let attrs = &item.attrs;
+    let body = &f.block;
+
+    Ok(quote! {
+        #(#attrs)*
+        impl #impl_generics ::handle::Handle<'__handle, #cx> for #self_ty #where_clause {
+            type Output = #output;
+
+            fn call(
+                &'__handle self,
+                #pat: &'__handle mut #cx,
+            ) -> ::handle::BoxFuture<'__handle, Self::Output> {
+                ::handle::__private::Box::pin(async move #body)
+            }
+        }
+    })
+}
@@ -0,0 +1,150 @@
+use alloc::{boxed::Box, vec::Vec};
+use core::fmt;
+
+use crate::{BoxFuture, ErasedHandle, Handle, MaybeSend};
+
+/// Runs all the handlers in sequence, even after some of them fail, and
+/// returns all the errors.
+///
+/// It suits validations, where every failing check is reported at once
+/// instead of stopping at the first one. The errors are returned in the order
+/// of the handlers.
+pub struct AllErrors<Context, E> {
+    handlers: Vec<ErasedHandle<Context, Result<(), E>>>,
+}
+
+impl<Context, E> AllErrors<Context, E> {
+    /// Creates a new [`AllErrors`].
+    #[inline]
+    pub fn new(handlers: Vec<ErasedHandle<Context, Result<(), E>>>) -> Self {
+        Self { handlers }
+    }
+}
+
+impl<Context, E> Clone for AllErrors<Context, E> {
+    fn clone(&self) -> Self {
+        Self::new(self.handlers.clone())
+    }
+}
+
+impl<Context, E> fmt::Debug for AllErrors<Context, E>
+where
+    Context: 'static,
+    E: 'static,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AllErrors")
+            .field("handlers", &self.handlers)
+            .finish()
+    }
+}
+
+impl<'a, Context, E> Handle<'a, Context> for AllErrors<Context, E>
+where
+    Context: MaybeSend + 'static,
+    E: MaybeSend + 'static,
+{
+    type Output = Result<(), Vec<E>>;
+
+    fn call(&'a self, cx: &'a mut Context) -> BoxFuture<'a, Self::Output> {
+        Box::pin(async move {
+            let mut errors = Vec::new();
+            for h in &self.handlers {
+                if let Err(e) = h.call(&mut *cx).await {
+                    errors.push(e);
+                }
+            }
+            if errors.is_empty() {
+                Ok(())
+            } else {
+                Err(errors)
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{AllErrors, ErasedHandle, Handle};
+    use futures::executor::block_on;
+
+    #[derive(Default)]
+    struct Form {
+        name: &'static str,
+        email: &'static str,
+        age: u8,
+        checked: usize,
+    }
+
+    async fn name(cx: &mut Form) -> Result<(), &'static str> {
+        cx.checked += 1;
+        if cx.name.is_empty() {
+            return Err("name is empty");
+        }
+        Ok(())
+    }
+
+    async fn name_length(cx: &mut Form) -> Result<(), &'static str> {
+        cx.checked += 1;
+        if cx.name.len() > 32 {
+            return Err("name is too long");
+        }
+        Ok(())
+    }
+
+    async fn email(cx: &mut Form) -> Result<(), &'static str> {
+        cx.checked += 1;
+        if !cx.email.contains('@') {
+            return Err("email is invalid");
+        }
+        Ok(())
+    }
+
+    async fn email_length(cx: &mut Form) -> Result<(), &'static str> {
+        cx.checked += 1;
+        if cx.email.len() > 64 {
+            return Err("email is too long");
+        }
+        Ok(())
+    }
+
+    async fn age(cx: &mut Form) -> Result<(), &'static str> {
+        cx.checked += 1;
+        if cx.age < 18 {
+            return Err("age is under 18");
+        }
+        Ok(())
+    }
+
+    fn validate() -> AllErrors<Form, &'static str> {
+        AllErrors::new(vec![
+            ErasedHandle::new(name),
+            ErasedHandle::new(name_length),
+            ErasedHandle::new(email),
+            ErasedHandle::new(email_length),
+            ErasedHandle::new(age),
+        ])
+    }
+
+    #[test]
+    fn collects_in_order() {
+        let mut cx = Form::default();
+        assert_eq!(
+            block_on(validate().call(&mut cx)),
+            Err(vec!["name is empty", "email is invalid", "age is under 18"])
+        );
+        assert_eq!(cx.checked, 5);
+    }
+
+    #[test]
+    fn all_ok() {
+        let mut cx = Form {
+            name: "alice",
+            email: "alice@example.com",
+            age: 30,
+            ..Default::default()
+        };
+        assert_eq!(block_on(validate().call(&mut cx)), Ok(()));
+        assert_eq!(cx.checked, 5);
+    }
+}
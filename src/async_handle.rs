@@ -0,0 +1,90 @@
+use crate::{BoxFuture, Handle};
+
+/// A handler written with [`macro@async_trait::async_trait`].
+///
+/// Implement it for middleware ported from an `#[async_trait]` code base, then
+/// wrap it in [`FromAsyncTrait`] to run it as a [`Handle`].
+#[async_trait::async_trait]
+pub trait AsyncHandle<Context: Send>: Send + Sync + 'static {
+    /// The returned type after the call operator is used.
+    type Output;
+
+    /// Invokes the handler within the given `Context` and then returns `Output`.
+    async fn call(&self, cx: &mut Context) -> Self::Output;
+}
+
+/// Runs an [`AsyncHandle`] as a [`Handle`].
+#[derive(Debug, Clone)]
+pub struct FromAsyncTrait<T>(pub T);
+
+impl<'a, Context, T> Handle<'a, Context> for FromAsyncTrait<T>
+where
+    T: AsyncHandle<Context>,
+    Context: Send,
+{
+    type Output = T::Output;
+
+    #[inline]
+    fn call(&'a self, cx: &'a mut Context) -> BoxFuture<'a, Self::Output> {
+        self.0.call(cx)
+    }
+
+    #[inline]
+    fn name(&self) -> &str {
+        std::any::type_name::<T>()
+    }
+}
+
+// The context holds `ArcHandle`s, which are `Send` only with `send`.
+#[cfg(all(test, feature = "send"))]
+mod tests {
+    use crate::{ArcHandle, AsyncHandle, FromAsyncTrait};
+    use futures::executor::block_on;
+    use std::sync::Arc;
+
+    type Result = anyhow::Result<()>;
+
+    #[derive(Default)]
+    struct Context {
+        trace: Vec<&'static str>,
+        middleware: Vec<ArcHandle<Context, Result>>,
+    }
+
+    impl Context {
+        async fn next(&mut self) -> Result {
+            if let Some(m) = self.middleware.pop() {
+                m.call(self).await
+            } else {
+                Ok(())
+            }
+        }
+    }
+
+    struct Legacy;
+
+    #[async_trait::async_trait]
+    impl AsyncHandle<Context> for Legacy {
+        type Output = Result;
+
+        async fn call(&self, cx: &mut Context) -> Result {
+            cx.trace.push("legacy");
+            cx.next().await
+        }
+    }
+
+    async fn native(cx: &mut Context) -> Result {
+        cx.trace.push("native");
+        cx.next().await
+    }
+
+    #[test]
+    fn mixed() {
+        let mut cx = Context {
+            middleware: vec![Arc::new(native), Arc::new(FromAsyncTrait(Legacy))],
+            ..Default::default()
+        };
+        assert!(block_on(cx.next()).is_ok());
+        assert_eq!(cx.trace, ["legacy", "native"]);
+        assert!(cx.middleware.is_empty());
+    }
+}
@@ -0,0 +1,80 @@
+use crate::{BoxFuture, Handle, MaybeSend, MaybeSync};
+
+/// Calls one of two handlers, chosen by a predicate on the context at each
+/// call.
+#[derive(Debug, Clone)]
+pub struct Branch<P, A, B> {
+    pred: P,
+    a: A,
+    b: B,
+}
+
+impl<P, A, B> Branch<P, A, B> {
+    /// Creates a new [`Branch`].
+    #[inline]
+    pub const fn new(pred: P, a: A, b: B) -> Self {
+        Self { pred, a, b }
+    }
+}
+
+/// Creates a [`Branch`] calling `if_true` when `pred` holds for the context,
+/// `if_false` otherwise.
+#[inline]
+pub const fn branch<P, A, B>(pred: P, if_true: A, if_false: B) -> Branch<P, A, B> {
+    Branch::new(pred, if_true, if_false)
+}
+
+impl<'a, Context, P, A, B> Handle<'a, Context> for Branch<P, A, B>
+where
+    P: Fn(&Context) -> bool + MaybeSend + MaybeSync + 'static,
+    A: Handle<'a, Context>,
+    B: Handle<'a, Context, Output = A::Output>,
+{
+    type Output = A::Output;
+
+    #[inline]
+    fn call(&'a self, cx: &'a mut Context) -> BoxFuture<'a, Self::Output> {
+        if (self.pred)(cx) {
+            self.a.call(cx)
+        } else {
+            self.b.call(cx)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{branch, BoxHandle};
+    use futures::executor::block_on;
+
+    #[derive(Default)]
+    struct Context {
+        admin: bool,
+        trace: Vec<&'static str>,
+    }
+
+    async fn admin(cx: &mut Context) -> Option<&'static str> {
+        cx.trace.push("admin");
+        Some("dashboard")
+    }
+
+    async fn guest(cx: &mut Context) -> Option<&'static str> {
+        cx.trace.push("guest");
+        Some("login")
+    }
+
+    #[test]
+    fn flips_per_call() {
+        let h: BoxHandle<Context, Option<&'static str>> =
+            Box::new(branch(|cx: &Context| cx.admin, admin, guest));
+        let mut cx = Context::default();
+
+        assert_eq!(block_on(h.call(&mut cx)), Some("login"));
+        assert_eq!(cx.trace, ["guest"]);
+
+        cx.admin = true;
+        cx.trace.clear();
+        assert_eq!(block_on(h.call(&mut cx)), Some("dashboard"));
+        assert_eq!(cx.trace, ["admin"]);
+    }
+}
@@ -0,0 +1,243 @@
+use std::{
+    cmp::Ordering,
+    error::Error,
+    fmt,
+    time::{Duration, Instant},
+};
+
+use crate::{BoxFuture, Handle, MaybeSend, MaybeSync};
+
+/// The time left for a request, as a deadline.
+///
+/// A budget too large to be represented as an [`Instant`] is unbounded, it
+/// never expires.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Budget {
+    deadline: Option<Instant>,
+}
+
+impl Budget {
+    /// Creates a budget of `duration` from now.
+    #[inline]
+    pub fn new(duration: Duration) -> Self {
+        Self {
+            deadline: Instant::now().checked_add(duration),
+        }
+    }
+
+    /// Creates a budget ending at the `deadline`.
+    #[inline]
+    pub const fn until(deadline: Instant) -> Self {
+        Self {
+            deadline: Some(deadline),
+        }
+    }
+
+    /// Creates a budget which never expires.
+    #[inline]
+    pub const fn unbounded() -> Self {
+        Self { deadline: None }
+    }
+
+    /// Returns the deadline, `None` if the budget is unbounded.
+    #[inline]
+    pub const fn deadline(&self) -> Option<Instant> {
+        self.deadline
+    }
+
+    /// Returns the time left, zero once expired and [`Duration::MAX`] if the
+    /// budget is unbounded.
+    #[inline]
+    pub fn remaining(&self) -> Duration {
+        self.deadline.map_or(Duration::MAX, |deadline| {
+            deadline.saturating_duration_since(Instant::now())
+        })
+    }
+
+    /// Returns `true` if no time is left.
+    #[inline]
+    pub fn expired(&self) -> bool {
+        self.remaining().is_zero()
+    }
+
+    /// Returns a budget of a `fraction` of the time left, e.g. for a retry.
+    ///
+    /// The `fraction` is clamped to `0.0..=1.0`. A non-zero fraction of an
+    /// unbounded budget is unbounded.
+    pub fn child(&self, fraction: f64) -> Self {
+        let fraction = if fraction.is_nan() {
+            0.0
+        } else {
+            fraction.clamp(0.0, 1.0)
+        };
+        let now = Instant::now();
+        match self.deadline {
+            Some(deadline) => {
+                let remaining = deadline.saturating_duration_since(now);
+                Self::until(now + remaining.mul_f64(fraction))
+            }
+            None if fraction == 0.0 => Self::until(now),
+            None => Self::unbounded(),
+        }
+    }
+}
+
+impl PartialOrd for Budget {
+    #[inline]
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Orders the budgets by deadline, an unbounded budget is the greatest.
+impl Ord for Budget {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self.deadline, other.deadline) {
+            (Some(a), Some(b)) => a.cmp(&b),
+            (Some(_), None) => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (None, None) => Ordering::Equal,
+        }
+    }
+}
+
+/// The error returned by [`WithBudget`] when its budget has expired.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BudgetExhausted;
+
+impl fmt::Display for BudgetExhausted {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("budget exhausted")
+    }
+}
+
+impl Error for BudgetExhausted {}
+
+/// Fails fast with [`BudgetExhausted`] instead of calling the handler once the
+/// budget of the request has expired.
+///
+/// The budget is read from the context with `f` on each call, so a pipeline
+/// reused across requests checks the budget of each one. It only checks the
+/// budget before the call, the handler reads the time left from the context
+/// to bound its own work.
+#[derive(Debug, Clone)]
+pub struct WithBudget<H, F> {
+    h: H,
+    f: F,
+}
+
+impl<H, F> WithBudget<H, F> {
+    /// Creates a new [`WithBudget`] reading the budget of the request with
+    /// `f`.
+    #[inline]
+    pub const fn new(h: H, f: F) -> Self {
+        Self { h, f }
+    }
+}
+
+impl<'a, Context, H, F, T, E> Handle<'a, Context> for WithBudget<H, F>
+where
+    H: Handle<'a, Context, Output = Result<T, E>>,
+    F: Fn(&Context) -> Budget + MaybeSend + MaybeSync + 'static,
+    E: From<BudgetExhausted> + MaybeSend + 'a,
+    T: MaybeSend + 'a,
+{
+    type Output = Result<T, E>;
+
+    fn call(&'a self, cx: &'a mut Context) -> BoxFuture<'a, Self::Output> {
+        if (self.f)(cx).expired() {
+            Box::pin(async { Err(BudgetExhausted.into()) })
+        } else {
+            self.h.call(cx)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Budget, BudgetExhausted, ContextExt, Next, Pipeline, WithBudget};
+    use futures::executor::block_on;
+    use std::time::{Duration, Instant};
+
+    type Result = anyhow::Result<()>;
+
+    struct Context {
+        budget: Budget,
+        calls: usize,
+        seen: Option<Duration>,
+        next: Next<Self, Result>,
+    }
+
+    impl Context {
+        fn new(budget: Budget) -> Self {
+            Self {
+                budget,
+                calls: 0,
+                seen: None,
+                next: Next::default(),
+            }
+        }
+    }
+
+    impl ContextExt<Result> for Context {
+        fn next_mut(&mut self) -> &mut Next<Self, Result> {
+            &mut self.next
+        }
+
+        fn next_ref(&self) -> &Next<Self, Result> {
+            &self.next
+        }
+    }
+
+    async fn fetch(cx: &mut Context) -> Result {
+        cx.calls += 1;
+        cx.seen = Some(cx.budget.remaining());
+        Ok(())
+    }
+
+    #[test]
+    fn per_request() {
+        let mut pipeline = Pipeline::new();
+        pipeline.push(WithBudget::new(fetch, |cx: &Context| cx.budget));
+
+        // The same pipeline serves requests with their own budgets.
+        for _ in 0..3 {
+            let mut cx = Context::new(Budget::until(Instant::now()));
+            let e = block_on(pipeline.run(&mut cx)).unwrap_err();
+            assert_eq!(e.downcast_ref::<BudgetExhausted>(), Some(&BudgetExhausted));
+            assert_eq!(cx.calls, 0);
+
+            let mut cx = Context::new(Budget::new(Duration::from_secs(60)));
+            assert!(block_on(pipeline.run(&mut cx)).is_ok());
+            assert_eq!(cx.calls, 1);
+            assert!(cx.seen.unwrap() <= Duration::from_secs(60));
+        }
+    }
+
+    #[test]
+    fn child() {
+        let budget = Budget::new(Duration::from_secs(60));
+        let child = budget.child(0.5);
+        let half = budget.remaining() / 2;
+
+        assert!(child < budget);
+        assert!(child.remaining() <= half + Duration::from_millis(100));
+        assert!(child.remaining() + Duration::from_millis(100) >= half);
+
+        assert!(budget.child(0.0).expired());
+        assert!(budget.child(2.0) <= budget);
+    }
+
+    #[test]
+    fn unbounded() {
+        let budget = Budget::new(Duration::MAX);
+        assert_eq!(budget, Budget::unbounded());
+        assert_eq!(budget.deadline(), None);
+        assert_eq!(budget.remaining(), Duration::MAX);
+        assert!(!budget.expired());
+
+        assert!(Budget::new(Duration::from_secs(60)) < budget);
+        assert_eq!(budget.child(0.5), budget);
+        assert!(budget.child(0.0).expired());
+    }
+}
@@ -0,0 +1,188 @@
+use std::{
+    error::Error,
+    fmt,
+    future::{poll_fn, Future},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex, PoisonError,
+    },
+    task::{Poll, Waker},
+};
+
+/// The error returned when a pipeline is cancelled before it completes, see
+/// [`Pipeline::run_until_cancelled`](crate::Pipeline::run_until_cancelled).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cancelled;
+
+impl fmt::Display for Cancelled {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("pipeline cancelled")
+    }
+}
+
+impl Error for Cancelled {}
+
+/// An output which can carry a [`Cancelled`] error.
+pub trait FromCancelled {
+    /// Converts the error into the output.
+    fn from_cancelled(e: Cancelled) -> Self;
+}
+
+impl<T, E> FromCancelled for Result<T, E>
+where
+    E: From<Cancelled>,
+{
+    #[inline]
+    fn from_cancelled(e: Cancelled) -> Self {
+        Err(e.into())
+    }
+}
+
+#[derive(Default)]
+struct Inner {
+    cancelled: AtomicBool,
+    wakers: Mutex<Vec<Waker>>,
+}
+
+/// A token to cancel a running pipeline, cheap to clone.
+///
+/// All the clones share the same state, cancelling one cancels them all.
+#[derive(Clone, Default)]
+pub struct CancelToken {
+    inner: Arc<Inner>,
+}
+
+impl CancelToken {
+    /// Creates a new [`CancelToken`].
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Cancels the token and wakes the tasks waiting on
+    /// [`CancelToken::cancelled`].
+    pub fn cancel(&self) {
+        self.inner.cancelled.store(true, Ordering::Release);
+        let wakers = std::mem::take(
+            &mut *self
+                .inner
+                .wakers
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner),
+        );
+        wakers.into_iter().for_each(Waker::wake);
+    }
+
+    /// Returns `true` if the token has been cancelled.
+    #[inline]
+    pub fn is_cancelled(&self) -> bool {
+        self.inner.cancelled.load(Ordering::Acquire)
+    }
+
+    /// Waits until the token is cancelled.
+    pub fn cancelled(&self) -> impl Future<Output = ()> + Send + '_ {
+        poll_fn(|cx| {
+            if self.is_cancelled() {
+                return Poll::Ready(());
+            }
+            let mut wakers = self
+                .inner
+                .wakers
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner);
+            // Checks again under the lock, `cancel` may have drained the wakers.
+            if self.is_cancelled() {
+                return Poll::Ready(());
+            }
+            if !wakers.iter().any(|w| w.will_wake(cx.waker())) {
+                wakers.push(cx.waker().clone());
+            }
+            Poll::Pending
+        })
+    }
+}
+
+impl fmt::Debug for CancelToken {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CancelToken")
+            .field("cancelled", &self.is_cancelled())
+            .finish()
+    }
+}
+
+/// The token of a cancellable run and how to report it was cancelled.
+pub(crate) type Cancel<Output> = (CancelToken, fn(Cancelled) -> Output);
+
+#[cfg(test)]
+mod tests {
+    use crate::{CancelToken, Cancelled, ContextExt, Next, Pipeline};
+    use futures::executor::block_on;
+    use std::time::Duration;
+
+    type Result = anyhow::Result<()>;
+
+    #[derive(Default)]
+    struct Context {
+        trace: Vec<&'static str>,
+        token: CancelToken,
+        next: Next<Self, Result>,
+    }
+
+    impl ContextExt<Result> for Context {
+        fn next_mut(&mut self) -> &mut Next<Self, Result> {
+            &mut self.next
+        }
+
+        fn next_ref(&self) -> &Next<Self, Result> {
+            &self.next
+        }
+    }
+
+    async fn one(cx: &mut Context) -> Result {
+        cx.trace.push("one>");
+        let output = cx.next().await;
+        cx.trace.push("one<");
+        output
+    }
+
+    // The client disconnects while the second handler runs.
+    async fn two(cx: &mut Context) -> Result {
+        cx.trace.push("two>");
+        cx.token.cancel();
+        let output = cx.next().await;
+        cx.trace.push("two<");
+        output
+    }
+
+    async fn three(cx: &mut Context) -> Result {
+        cx.trace.push("three");
+        cx.next().await
+    }
+
+    #[test]
+    fn cancel_between_handlers() {
+        let mut pipeline = Pipeline::new();
+        pipeline.push(one).push(two).push(three);
+
+        let mut cx = Context::default();
+        let token = cx.token.clone();
+        let e = block_on(pipeline.run_until_cancelled(&mut cx, &token)).unwrap_err();
+        assert_eq!(e.downcast_ref::<Cancelled>(), Some(&Cancelled));
+        assert_eq!(cx.trace, ["one>", "two>", "two<", "one<"]);
+    }
+
+    #[async_std::test]
+    async fn wait_cancelled() {
+        let token = CancelToken::new();
+        let task = async_std::task::spawn({
+            let token = token.clone();
+            async move { token.cancelled().await }
+        });
+
+        async_std::task::sleep(Duration::from_millis(10)).await;
+        assert!(!token.is_cancelled());
+        token.cancel();
+        task.await;
+        assert!(token.is_cancelled());
+    }
+}
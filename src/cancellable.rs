@@ -0,0 +1,121 @@
+use tokio_util::sync::CancellationToken;
+
+use crate::{BoxFuture, Cancelled, FromCancelled, Handle, MaybeSend};
+
+/// Races the handler against a [`CancellationToken`].
+///
+/// When the token is cancelled first, the future of the handler is dropped
+/// wherever it is suspended and the output carries a [`Cancelled`] error.
+#[derive(Debug, Clone)]
+pub struct Cancellable<H> {
+    h: H,
+    token: CancellationToken,
+}
+
+impl<H> Cancellable<H> {
+    /// Creates a new [`Cancellable`].
+    #[inline]
+    pub const fn new(h: H, token: CancellationToken) -> Self {
+        Self { h, token }
+    }
+
+    /// Returns the token of the handler.
+    #[inline]
+    pub const fn token(&self) -> &CancellationToken {
+        &self.token
+    }
+}
+
+impl<'a, Context, H> Handle<'a, Context> for Cancellable<H>
+where
+    H: Handle<'a, Context>,
+    H::Output: FromCancelled + MaybeSend + 'a,
+{
+    type Output = H::Output;
+
+    fn call(&'a self, cx: &'a mut Context) -> BoxFuture<'a, Self::Output> {
+        let fut = self.h.call(cx);
+
+        Box::pin(async move {
+            tokio::select! {
+                // A token cancelled before the call wins over a ready handler.
+                biased;
+                () = self.token.cancelled() => H::Output::from_cancelled(Cancelled),
+                output = fut => output,
+            }
+        })
+    }
+
+    #[inline]
+    fn name(&self) -> &str {
+        self.h.name()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Cancellable, Cancelled, Handle};
+    use std::{
+        sync::atomic::{AtomicBool, Ordering},
+        time::{Duration, Instant},
+    };
+    use tokio::time::sleep;
+    use tokio_util::sync::CancellationToken;
+
+    type Result = std::result::Result<usize, Cancelled>;
+
+    static DROPPED: AtomicBool = AtomicBool::new(false);
+
+    struct Guard;
+
+    impl Drop for Guard {
+        fn drop(&mut self) {
+            DROPPED.store(true, Ordering::SeqCst);
+        }
+    }
+
+    #[derive(Default)]
+    struct Context {
+        steps: usize,
+    }
+
+    async fn slow(cx: &mut Context) -> Result {
+        let _guard = Guard;
+        cx.steps += 1;
+        sleep(Duration::from_secs(10)).await;
+        cx.steps += 1;
+        Ok(cx.steps)
+    }
+
+    async fn fast(cx: &mut Context) -> Result {
+        cx.steps += 1;
+        Ok(cx.steps)
+    }
+
+    #[tokio::test]
+    async fn cancelled_at_await() {
+        let token = CancellationToken::new();
+        let h = Cancellable::new(slow, token.clone());
+
+        let start = Instant::now();
+        tokio::spawn(async move {
+            sleep(Duration::from_millis(20)).await;
+            token.cancel();
+        });
+
+        let mut cx = Context::default();
+        assert_eq!(h.call(&mut cx).await, Err(Cancelled));
+        assert!(DROPPED.load(Ordering::SeqCst));
+        assert!(start.elapsed() < Duration::from_secs(1));
+        assert_eq!(cx.steps, 1);
+    }
+
+    #[tokio::test]
+    async fn completes() {
+        let h = Cancellable::new(fast, CancellationToken::new());
+        assert_eq!(h.call(&mut Context::default()).await, Ok(1));
+
+        h.token().cancel();
+        assert_eq!(h.call(&mut Context::default()).await, Err(Cancelled));
+    }
+}
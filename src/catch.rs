@@ -0,0 +1,97 @@
+use alloc::boxed::Box;
+use core::future::Future;
+
+use crate::{BoxFuture, Handle, MaybeSend, MaybeSync};
+
+/// Catches the error returned by the handler and recovers it with a closure.
+///
+/// The output is the one of the closure, the `Ok` of the handler is converted
+/// into it, so no error escapes.
+///
+/// The closure gets the [`NextAlreadyCalled`](crate::NextAlreadyCalled) error
+/// from [`ContextExt::next`](crate::ContextExt::next) when the handler has
+/// already called it.
+#[derive(Debug, Clone)]
+pub struct Catch<H, F> {
+    h: H,
+    f: F,
+}
+
+impl<H, F> Catch<H, F> {
+    /// Creates a new [`Catch`].
+    #[inline]
+    pub const fn new(h: H, f: F) -> Self {
+        Self { h, f }
+    }
+}
+
+impl<'a, Context, H, F, Fut, T, E, O> Handle<'a, Context> for Catch<H, F>
+where
+    H: for<'b> Handle<'b, Context, Output = Result<T, E>>,
+    F: Fn(&'a mut Context, E) -> Fut + MaybeSend + MaybeSync + 'static,
+    Fut: Future<Output = O> + MaybeSend + 'a,
+    Context: MaybeSend + 'a,
+    T: Into<O> + MaybeSend + 'a,
+    E: MaybeSend + 'a,
+{
+    type Output = O;
+
+    fn call(&'a self, cx: &'a mut Context) -> BoxFuture<'a, Self::Output> {
+        Box::pin(async move {
+            // Reborrows the context, it is released when the inner future completes.
+            match self.h.call(&mut *cx).await {
+                Ok(t) => t.into(),
+                Err(e) => (self.f)(cx, e).await,
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Handle, HandleExt};
+    use anyhow::{anyhow, Result};
+    use futures::executor::block_on;
+
+    #[derive(Default)]
+    struct Context {
+        body: String,
+        recovered: usize,
+    }
+
+    async fn fail(cx: &mut Context) -> Result<u16> {
+        cx.body.push_str("fail");
+        Err(anyhow!("boom"))
+    }
+
+    async fn ok(cx: &mut Context) -> Result<u16> {
+        cx.body.push_str("ok");
+        Ok(200)
+    }
+
+    async fn recover(cx: &mut Context, e: anyhow::Error) -> u16 {
+        cx.recovered += 1;
+        cx.body = format!("recovered: {}", e);
+        500
+    }
+
+    #[test]
+    fn catch_err() {
+        let mut cx = Context::default();
+        let h = fail.catch(recover);
+
+        assert_eq!(block_on(h.call(&mut cx)), 500);
+        assert_eq!(cx.recovered, 1);
+        assert_eq!(cx.body, "recovered: boom");
+    }
+
+    #[test]
+    fn catch_ok() {
+        let mut cx = Context::default();
+        let h = ok.catch(recover);
+
+        assert_eq!(block_on(h.call(&mut cx)), 200);
+        assert_eq!(cx.recovered, 0);
+        assert_eq!(cx.body, "ok");
+    }
+}
@@ -0,0 +1,106 @@
+use core::ops::Shr;
+
+use crate::{BoxFuture, Handle, TryTuple};
+
+/// Runs the handler `A`, then `B` only if `A` succeeded, see
+/// [`HandleExt::chain`](crate::HandleExt::chain).
+///
+/// It runs as the `(A, B)` [`TryTuple`], which it only extends with `>>`:
+/// `auth.chain(log) >> business` builds `Chain<Chain<Auth, Log>, Business>`.
+/// The operator can't be implemented for tuples, plain functions and other
+/// foreign types, so a chain starts with
+/// [`HandleExt::chain`](crate::HandleExt::chain).
+///
+/// Both handlers run on the cursor of the pipeline: once `A` has called
+/// [`ContextExt::next`](crate::ContextExt::next), `B` gets the
+/// [`NextAlreadyCalled`](crate::NextAlreadyCalled) error if it calls it too.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Chain<A, B>(TryTuple<(A, B)>);
+
+impl<A, B> Chain<A, B> {
+    /// Creates a new [`Chain`].
+    #[inline]
+    pub const fn new(a: A, b: B) -> Self {
+        Self(TryTuple::new((a, b)))
+    }
+
+    /// Returns the handlers as a tuple.
+    #[inline]
+    pub fn into_inner(self) -> (A, B) {
+        self.0.into_inner()
+    }
+}
+
+impl<A, B, H> Shr<H> for Chain<A, B> {
+    type Output = Chain<Self, H>;
+
+    #[inline]
+    fn shr(self, h: H) -> Self::Output {
+        Chain::new(self, h)
+    }
+}
+
+impl<'a, Context, A, B> Handle<'a, Context> for Chain<A, B>
+where
+    TryTuple<(A, B)>: Handle<'a, Context>,
+{
+    type Output = <TryTuple<(A, B)> as Handle<'a, Context>>::Output;
+
+    #[inline]
+    fn call(&'a self, cx: &'a mut Context) -> BoxFuture<'a, Self::Output> {
+        self.0.call(cx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Handle, HandleExt};
+    use futures::executor::block_on;
+
+    type Result = std::result::Result<usize, &'static str>;
+
+    #[derive(Default)]
+    struct Context {
+        user: Option<&'static str>,
+        trace: Vec<&'static str>,
+    }
+
+    async fn auth(cx: &mut Context) -> Result {
+        cx.trace.push("auth");
+        cx.user.map(str::len).ok_or("anonymous")
+    }
+
+    async fn log(cx: &mut Context) -> Result {
+        cx.trace.push("log");
+        Ok(cx.trace.len())
+    }
+
+    async fn business(cx: &mut Context) -> Result {
+        cx.trace.push("business");
+        Ok(cx.trace.len())
+    }
+
+    async fn sequential(cx: &mut Context) -> Result {
+        auth(cx).await?;
+        log(cx).await?;
+        business(cx).await
+    }
+
+    #[test]
+    fn same_as_sequential() {
+        let chain = auth.chain(log) >> business;
+
+        for user in [Some("alice"), None] {
+            let mut a = Context {
+                user,
+                ..Default::default()
+            };
+            let mut b = Context {
+                user,
+                ..Default::default()
+            };
+            assert_eq!(block_on(chain.call(&mut a)), block_on(sequential(&mut b)));
+            assert_eq!(a.trace, b.trace);
+        }
+    }
+}
@@ -0,0 +1,119 @@
+use crate::Handle;
+use alloc::boxed::Box;
+
+/// A boxed [`Handle`] trait object which can be cloned.
+pub type BoxCloneHandle<Context, Output> = Box<dyn CloneHandle<Context, Output>>;
+
+/// A [`Handle`] which can be cloned behind a trait object.
+///
+/// It is implemented for all the handlers which implement [`Clone`].
+pub trait CloneHandle<Context, Output>: for<'a> Handle<'a, Context, Output = Output> {
+    /// Clones the handler into a new [`BoxCloneHandle`].
+    fn clone_box(&self) -> BoxCloneHandle<Context, Output>;
+}
+
+impl<Context, Output, H> CloneHandle<Context, Output> for H
+where
+    H: for<'a> Handle<'a, Context, Output = Output> + Clone,
+{
+    fn clone_box(&self) -> BoxCloneHandle<Context, Output> {
+        Box::new(self.clone())
+    }
+}
+
+impl<Context, Output> Clone for BoxCloneHandle<Context, Output>
+where
+    Context: 'static,
+    Output: 'static,
+{
+    fn clone(&self) -> Self {
+        self.clone_box()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{BoxCloneHandle, BoxFuture, Handle};
+    use futures::executor::block_on;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    type Result = anyhow::Result<()>;
+
+    #[derive(Default)]
+    struct Context {
+        trace: Vec<&'static str>,
+        middleware: Vec<BoxCloneHandle<Context, Result>>,
+    }
+
+    impl Context {
+        async fn next(&mut self) -> Result {
+            if let Some(m) = self.middleware.pop() {
+                m.call(self).await
+            } else {
+                Ok(())
+            }
+        }
+    }
+
+    async fn a(cx: &mut Context) -> Result {
+        cx.trace.push("a");
+        cx.next().await
+    }
+
+    #[derive(Default)]
+    struct Counter {
+        hits: AtomicUsize,
+    }
+
+    impl Clone for Counter {
+        fn clone(&self) -> Self {
+            Self {
+                hits: AtomicUsize::new(self.hits.load(Ordering::Relaxed)),
+            }
+        }
+    }
+
+    impl<'a> Handle<'a, Context> for Counter {
+        type Output = Result;
+
+        fn call(&'a self, cx: &'a mut Context) -> BoxFuture<'a, Self::Output> {
+            Box::pin(async move {
+                self.hits.fetch_add(1, Ordering::Relaxed);
+                cx.trace.push("counter");
+                cx.next().await
+            })
+        }
+    }
+
+    fn hits(h: &BoxCloneHandle<Context, Result>) -> usize {
+        h.as_any()
+            .downcast_ref::<Counter>()
+            .unwrap()
+            .hits
+            .load(Ordering::Relaxed)
+    }
+
+    #[test]
+    fn clone_pipeline() {
+        let first: Vec<BoxCloneHandle<Context, Result>> =
+            vec![Box::new(Counter::default()), Box::new(a)];
+        let second = first.clone();
+
+        for middleware in [first.clone(), second.clone()] {
+            let mut cx = Context {
+                middleware,
+                ..Default::default()
+            };
+            assert!(block_on(cx.next()).is_ok());
+            assert_eq!(cx.trace, ["a", "counter"]);
+        }
+
+        // Each copy owns its own counter.
+        assert!(block_on(first[0].call(&mut Context::default())).is_ok());
+        assert!(block_on(first[0].call(&mut Context::default())).is_ok());
+        assert!(block_on(second[0].call(&mut Context::default())).is_ok());
+        assert_eq!(hits(&first[0]), 2);
+        assert_eq!(hits(&second[0]), 1);
+        assert_eq!(hits(&first[0].clone()), 2);
+    }
+}
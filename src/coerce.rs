@@ -0,0 +1,180 @@
+use alloc::boxed::Box;
+use core::{fmt, marker::PhantomData};
+
+use crate::{BoxFuture, Handle};
+
+/// Conversion of a handler output into the output of a pipeline, see
+/// [`coerce`].
+///
+/// It is implemented for every type converting [`Into`] the output. Implement
+/// it for the outputs which don't, such as a `Result` mapped into a response.
+pub trait IntoOutput<Output> {
+    /// Converts the value into the `Output`.
+    fn into_output(self) -> Output;
+}
+
+impl<T, Output> IntoOutput<Output> for T
+where
+    T: Into<Output>,
+{
+    #[inline]
+    fn into_output(self) -> Output {
+        self.into()
+    }
+}
+
+/// Converts the output of the handler `h` into `Output` with [`IntoOutput`].
+///
+/// The conversion runs once the handler's future completes, within the same
+/// boxed future, so handlers of different outputs join a single pipeline.
+#[inline]
+pub const fn coerce<Output, H>(h: H) -> Coerce<H, Output> {
+    Coerce::new(h)
+}
+
+/// The handler returned by [`coerce`].
+pub struct Coerce<H, Output> {
+    h: H,
+    _output: PhantomData<fn() -> Output>,
+}
+
+impl<H, Output> Coerce<H, Output> {
+    /// Creates a new [`Coerce`].
+    #[inline]
+    pub const fn new(h: H) -> Self {
+        Self {
+            h,
+            _output: PhantomData,
+        }
+    }
+}
+
+impl<H, Output> Clone for Coerce<H, Output>
+where
+    H: Clone,
+{
+    #[inline]
+    fn clone(&self) -> Self {
+        Self::new(self.h.clone())
+    }
+}
+
+impl<H, Output> fmt::Debug for Coerce<H, Output>
+where
+    H: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Coerce").field("h", &self.h).finish()
+    }
+}
+
+impl<'a, Context, H, Output> Handle<'a, Context> for Coerce<H, Output>
+where
+    H: Handle<'a, Context>,
+    H::Output: IntoOutput<Output>,
+    Output: 'static,
+{
+    type Output = Output;
+
+    fn call(&'a self, cx: &'a mut Context) -> BoxFuture<'a, Self::Output> {
+        let fut = self.h.call(cx);
+        Box::pin(async move { fut.await.into_output() })
+    }
+
+    #[inline]
+    fn name(&self) -> &str {
+        self.h.name()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::IntoOutput;
+    use crate::{coerce, BoxHandle, HandleExt};
+    use futures::executor::block_on;
+
+    #[derive(Debug, PartialEq)]
+    struct Response {
+        status: u16,
+        body: String,
+    }
+
+    impl From<u16> for Response {
+        fn from(status: u16) -> Self {
+            Self {
+                status,
+                body: String::new(),
+            }
+        }
+    }
+
+    impl From<&'static str> for Response {
+        fn from(body: &'static str) -> Self {
+            Self {
+                status: 200,
+                body: body.to_string(),
+            }
+        }
+    }
+
+    enum Denied {
+        Anonymous,
+        Banned(&'static str),
+    }
+
+    impl IntoOutput<Response> for Result<&'static str, Denied> {
+        fn into_output(self) -> Response {
+            match self {
+                Ok(body) => body.into(),
+                Err(Denied::Anonymous) => 401.into(),
+                Err(Denied::Banned(reason)) => Response {
+                    status: 403,
+                    body: reason.to_string(),
+                },
+            }
+        }
+    }
+
+    #[derive(Default)]
+    struct Context {
+        user: Option<&'static str>,
+    }
+
+    async fn health(_: &mut Context) -> u16 {
+        204
+    }
+
+    async fn index(_: &mut Context) -> &'static str {
+        "hello"
+    }
+
+    async fn admin(cx: &mut Context) -> Result<&'static str, Denied> {
+        match cx.user {
+            None => Err(Denied::Anonymous),
+            Some("mallory") => Err(Denied::Banned("spam")),
+            Some(_) => Ok("dashboard"),
+        }
+    }
+
+    #[test]
+    fn homogeneous() {
+        let routes: Vec<BoxHandle<Context, Response>> = vec![
+            Box::new(coerce(health)),
+            Box::new(index.coerce()),
+            Box::new(coerce::<Response, _>(admin)),
+        ];
+
+        let mut cx = Context::default();
+        let outputs: Vec<_> = routes.iter().map(|h| block_on(h.call(&mut cx))).collect();
+        assert_eq!(outputs, [204.into(), "hello".into(), 401.into()]);
+
+        cx.user = Some("mallory");
+        assert_eq!(
+            block_on(routes[2].call(&mut cx)),
+            Response {
+                status: 403,
+                body: "spam".to_string(),
+            }
+        );
+    }
+}
@@ -0,0 +1,169 @@
+use core::{
+    fmt,
+    ops::{Deref, DerefMut},
+};
+
+use crate::{ContextExt, MaybeSend, Next};
+
+mod sealed {
+    pub trait Sealed<Output> {}
+
+    impl<Context, Output> Sealed<Output> for Context where Context: crate::ContextExt<Output> {}
+}
+
+/// Stops the running [`Pipeline`](crate::Pipeline) without an error.
+///
+/// It is implemented for all the contexts implementing [`ContextExt`], wrap a
+/// context in a [`Stoppable`] to add a cursor to it.
+///
+/// Once stopped, [`ContextExt::next`] returns [`Empty::empty`](crate::Empty)
+/// instead of calling the remaining handlers. The handlers already running
+/// still complete their code after `next`. Stopping a nested pipeline does not
+/// stop the outer one.
+pub trait PipelineControl<Output>: sealed::Sealed<Output> {
+    /// Stops the running pipeline.
+    fn stop(&mut self);
+
+    /// Returns `true` if the running pipeline has been stopped.
+    fn is_stopped(&self) -> bool;
+}
+
+impl<Context, Output> PipelineControl<Output> for Context
+where
+    Context: ContextExt<Output>,
+{
+    #[inline]
+    fn stop(&mut self) {
+        self.next_mut().stop();
+    }
+
+    #[inline]
+    fn is_stopped(&self) -> bool {
+        self.next_ref().is_stopped()
+    }
+}
+
+/// Wraps a context, adding the [`Next`] cursor to it.
+///
+/// It dereferences to the wrapped context.
+pub struct Stoppable<Context, Output> {
+    cx: Context,
+    next: Next<Self, Output>,
+}
+
+impl<Context, Output> Stoppable<Context, Output> {
+    /// Creates a new [`Stoppable`].
+    #[inline]
+    pub fn new(cx: Context) -> Self {
+        Self {
+            cx,
+            next: Next::default(),
+        }
+    }
+
+    /// Returns the wrapped context.
+    #[inline]
+    pub fn into_inner(self) -> Context {
+        self.cx
+    }
+}
+
+impl<Context, Output> ContextExt<Output> for Stoppable<Context, Output>
+where
+    Context: MaybeSend + 'static,
+    Output: 'static,
+{
+    #[inline]
+    fn next_mut(&mut self) -> &mut Next<Self, Output> {
+        &mut self.next
+    }
+
+    #[inline]
+    fn next_ref(&self) -> &Next<Self, Output> {
+        &self.next
+    }
+}
+
+impl<Context, Output> Deref for Stoppable<Context, Output> {
+    type Target = Context;
+
+    #[inline]
+    fn deref(&self) -> &Context {
+        &self.cx
+    }
+}
+
+impl<Context, Output> DerefMut for Stoppable<Context, Output> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut Context {
+        &mut self.cx
+    }
+}
+
+impl<Context, Output> fmt::Debug for Stoppable<Context, Output>
+where
+    Context: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Stoppable")
+            .field("cx", &self.cx)
+            .field("next", &self.next)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ContextExt, Pipeline, PipelineControl, Stoppable};
+    use futures::executor::block_on;
+
+    type Result = anyhow::Result<()>;
+
+    #[derive(Debug, Default)]
+    struct Request {
+        trace: Vec<&'static str>,
+        found: bool,
+    }
+
+    type Context = Stoppable<Request, Result>;
+
+    async fn log(cx: &mut Context) -> Result {
+        cx.trace.push("log>");
+        let output = cx.next().await;
+        cx.trace.push("log<");
+        output
+    }
+
+    async fn lookup(cx: &mut Context) -> Result {
+        cx.trace.push("lookup");
+        if !cx.found {
+            cx.stop();
+        }
+        cx.next().await
+    }
+
+    async fn render(cx: &mut Context) -> Result {
+        cx.trace.push("render");
+        cx.next().await
+    }
+
+    fn run(found: bool) -> Request {
+        let mut pipeline = Pipeline::new();
+        pipeline.push(log).push(lookup).push(render);
+
+        let mut cx = Stoppable::new(Request {
+            found,
+            ..Default::default()
+        });
+        assert!(block_on(pipeline.run(&mut cx)).is_ok());
+        // The stop only applies to the run it happened in.
+        assert!(!cx.is_stopped());
+        cx.into_inner()
+    }
+
+    #[test]
+    fn stop() {
+        assert_eq!(run(true).trace, ["log>", "lookup", "render", "log<"]);
+        assert_eq!(run(false).trace, ["log>", "lookup", "log<"]);
+    }
+}
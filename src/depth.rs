@@ -0,0 +1,104 @@
+use core::{error::Error, fmt};
+
+/// The error returned when the handlers of a pipeline nest deeper than its
+/// maximum depth, see [`Pipeline::max_depth`](crate::Pipeline::max_depth).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DepthExceeded {
+    /// The maximum depth of the pipeline.
+    pub max_depth: usize,
+}
+
+impl fmt::Display for DepthExceeded {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "pipeline exceeded its maximum depth of {}",
+            self.max_depth
+        )
+    }
+}
+
+impl Error for DepthExceeded {}
+
+/// An output which can carry a [`DepthExceeded`] error.
+pub trait FromDepthExceeded {
+    /// Converts the error into the output.
+    fn from_depth_exceeded(e: DepthExceeded) -> Self;
+}
+
+impl<T, E> FromDepthExceeded for Result<T, E>
+where
+    E: From<DepthExceeded>,
+{
+    #[inline]
+    fn from_depth_exceeded(e: DepthExceeded) -> Self {
+        Err(e.into())
+    }
+}
+
+/// The maximum depth of a pipeline and how to report it was exceeded.
+pub(crate) type MaxDepth<Output> = (usize, fn(DepthExceeded) -> Output);
+
+#[cfg(test)]
+mod tests {
+    use crate::{ContextExt, DepthExceeded, Next, Pipeline, Stack};
+    use futures::executor::block_on;
+    use std::cell::OnceCell;
+
+    type Result = anyhow::Result<()>;
+
+    #[derive(Default)]
+    struct Context {
+        hits: usize,
+        next: Next<Self, Result>,
+    }
+
+    impl ContextExt<Result> for Context {
+        fn next_mut(&mut self) -> &mut Next<Self, Result> {
+            &mut self.next
+        }
+
+        fn next_ref(&self) -> &Next<Self, Result> {
+            &self.next
+        }
+    }
+
+    thread_local! {
+        // Thread-local, so the handlers need not be `Sync` without `send`.
+        static STACK: OnceCell<Stack<Context, Result>> = const { OnceCell::new() };
+    }
+
+    fn stack() -> Stack<Context, Result> {
+        STACK.with(|stack| {
+            stack
+                .get_or_init(|| {
+                    let mut pipeline = Pipeline::new();
+                    pipeline.max_depth(5).push(reenter);
+                    pipeline.freeze()
+                })
+                .clone()
+        })
+    }
+
+    // Runs the whole pipeline again, forever.
+    async fn reenter(cx: &mut Context) -> Result {
+        cx.hits += 1;
+        stack().run(cx).await
+    }
+
+    #[test]
+    fn max_depth() {
+        let stack = stack();
+
+        for _ in 0..2 {
+            let mut cx = Context::default();
+            let e = block_on(stack.run(&mut cx)).unwrap_err();
+            assert_eq!(
+                e.downcast_ref::<DepthExceeded>(),
+                Some(&DepthExceeded { max_depth: 5 })
+            );
+            assert_eq!(cx.hits, 5);
+            assert_eq!(cx.next.depth(), 0);
+        }
+    }
+}
@@ -0,0 +1,163 @@
+use std::{collections::HashMap, fmt, hash::Hash, sync::Arc};
+
+use crate::{ArcHandle, BoxFuture, Empty, Handle, MaybeSend, MaybeSync};
+
+#[cfg(feature = "send")]
+type KeyFn<Context, K> = Arc<dyn Fn(&Context) -> K + Send + Sync>;
+
+#[cfg(not(feature = "send"))]
+type KeyFn<Context, K> = Arc<dyn Fn(&Context) -> K>;
+
+/// Routes the context to one of several handlers by a key extracted from it.
+///
+/// A key without a handler goes to the fallback handler, or returns
+/// [`Empty::empty`] when there is none.
+pub struct Dispatch<K, Context, Output> {
+    key: KeyFn<Context, K>,
+    routes: HashMap<K, ArcHandle<Context, Output>>,
+    fallback: Option<ArcHandle<Context, Output>>,
+}
+
+impl<K, Context, Output> Dispatch<K, Context, Output> {
+    /// Creates a new [`Dispatch`] from the `routes` and the `key` extracting
+    /// the key of the context.
+    pub fn new<F>(routes: HashMap<K, ArcHandle<Context, Output>>, key: F) -> Self
+    where
+        F: Fn(&Context) -> K + MaybeSend + MaybeSync + 'static,
+    {
+        Self {
+            key: Arc::new(key),
+            routes,
+            fallback: None,
+        }
+    }
+
+    /// Routes the `key` to the handler `h`, returning the handler it replaces.
+    pub fn insert<H>(&mut self, key: K, h: H) -> Option<ArcHandle<Context, Output>>
+    where
+        K: Eq + Hash,
+        H: for<'a> Handle<'a, Context, Output = Output>,
+    {
+        self.routes.insert(key, Arc::new(h))
+    }
+
+    /// Sets the handler called for the keys without a route.
+    pub fn fallback<H>(mut self, h: H) -> Self
+    where
+        H: for<'a> Handle<'a, Context, Output = Output>,
+    {
+        self.fallback = Some(Arc::new(h));
+        self
+    }
+}
+
+impl<'a, K, Context, Output> Handle<'a, Context> for Dispatch<K, Context, Output>
+where
+    K: Eq + Hash + MaybeSend + MaybeSync + 'static,
+    Context: 'static,
+    Output: Empty + MaybeSend + 'static,
+{
+    type Output = Output;
+
+    fn call(&'a self, cx: &'a mut Context) -> BoxFuture<'a, Self::Output> {
+        let key = (self.key)(cx);
+        match self.routes.get(&key).or(self.fallback.as_ref()) {
+            Some(h) => h.call(cx),
+            None => Box::pin(async { Output::empty() }),
+        }
+    }
+}
+
+impl<K, Context, Output> Clone for Dispatch<K, Context, Output>
+where
+    K: Clone,
+{
+    fn clone(&self) -> Self {
+        Self {
+            key: self.key.clone(),
+            routes: self.routes.clone(),
+            fallback: self.fallback.clone(),
+        }
+    }
+}
+
+impl<K, Context, Output> fmt::Debug for Dispatch<K, Context, Output>
+where
+    K: fmt::Debug,
+    Context: 'static,
+    Output: 'static,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Dispatch")
+            .field("routes", &self.routes)
+            .field("fallback", &self.fallback)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ArcHandle, ContextExt, Dispatch, Next, Pipeline};
+    use futures::executor::block_on;
+    use std::{collections::HashMap, sync::Arc};
+
+    type Output = Option<&'static str>;
+
+    #[derive(Default)]
+    struct Message {
+        kind: &'static str,
+        next: Next<Self, Output>,
+    }
+
+    impl ContextExt<Output> for Message {
+        fn next_mut(&mut self) -> &mut Next<Self, Output> {
+            &mut self.next
+        }
+
+        fn next_ref(&self) -> &Next<Self, Output> {
+            &self.next
+        }
+    }
+
+    async fn join(_: &mut Message) -> Output {
+        Some("joined")
+    }
+
+    async fn leave(_: &mut Message) -> Output {
+        Some("left")
+    }
+
+    async fn chat(_: &mut Message) -> Output {
+        Some("chatted")
+    }
+
+    async fn unknown(_: &mut Message) -> Output {
+        Some("unknown")
+    }
+
+    #[test]
+    fn routes_by_kind() {
+        let routes: HashMap<_, ArcHandle<Message, Output>> = HashMap::from([
+            ("join", Arc::new(join) as ArcHandle<_, _>),
+            ("leave", Arc::new(leave)),
+        ]);
+        let mut dispatch = Dispatch::new(routes, |cx: &Message| cx.kind);
+        dispatch.insert("chat", chat);
+
+        let mut pipeline = Pipeline::new();
+        pipeline.push(dispatch.fallback(unknown));
+
+        for (kind, output) in [
+            ("join", Some("joined")),
+            ("leave", Some("left")),
+            ("chat", Some("chatted")),
+            ("kick", Some("unknown")),
+        ] {
+            let mut cx = Message {
+                kind,
+                ..Default::default()
+            };
+            assert_eq!(block_on(pipeline.run(&mut cx)), output);
+        }
+    }
+}
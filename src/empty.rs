@@ -0,0 +1,47 @@
+use alloc::vec::Vec;
+use core::ops::ControlFlow;
+
+/// The output produced by a pipeline which has no handler left to call.
+pub trait Empty {
+    /// Returns the output of an exhausted pipeline.
+    fn empty() -> Self;
+}
+
+impl Empty for () {
+    #[inline]
+    fn empty() -> Self {}
+}
+
+impl<T, E> Empty for Result<T, E>
+where
+    T: Empty,
+{
+    #[inline]
+    fn empty() -> Self {
+        Ok(T::empty())
+    }
+}
+
+impl<T> Empty for Option<T> {
+    #[inline]
+    fn empty() -> Self {
+        None
+    }
+}
+
+impl<B, C> Empty for ControlFlow<B, C>
+where
+    C: Empty,
+{
+    #[inline]
+    fn empty() -> Self {
+        ControlFlow::Continue(C::empty())
+    }
+}
+
+impl<T> Empty for Vec<T> {
+    #[inline]
+    fn empty() -> Self {
+        Vec::new()
+    }
+}
@@ -0,0 +1,113 @@
+use alloc::boxed::Box;
+use core::future::Future;
+
+use crate::{BoxFuture, ContextExt, MaybeSend, MaybeSync, NextAlreadyCalled, Pipeline};
+
+/// A handler taking the context by value, run by [`Pipeline::run_owned`] as
+/// the last stage of a pipeline.
+///
+/// It can move large buffers out of the context instead of cloning them.
+pub trait Endpoint<Context>: MaybeSend + MaybeSync + 'static {
+    /// The type of value produced on completion.
+    type Output;
+
+    /// Consumes the `Context` and then returns `Output`.
+    fn call(&self, cx: Context) -> BoxFuture<'static, Self::Output>;
+}
+
+impl<Context, F, Fut> Endpoint<Context> for F
+where
+    F: Fn(Context) -> Fut + MaybeSend + MaybeSync + 'static,
+    Fut: Future + MaybeSend + 'static,
+{
+    type Output = Fut::Output;
+
+    fn call(&self, cx: Context) -> BoxFuture<'static, Self::Output> {
+        Box::pin((self)(cx))
+    }
+}
+
+impl<Context, E> Pipeline<Context, Result<(), E>> {
+    /// Runs the pipeline on the context, then hands the context over to the
+    /// `endpoint` when it succeeds.
+    ///
+    /// The handlers only borrow the context, so the endpoint can't run at the
+    /// position of [`ContextExt::next`]: it runs once every handler has
+    /// completed, including the code after their call to `next`. A handler
+    /// can't observe the output of the endpoint. When the pipeline fails, the
+    /// endpoint is not called and the error is returned.
+    pub fn run_owned<'a, D, T>(
+        &'a self,
+        mut cx: Context,
+        endpoint: &'a D,
+    ) -> BoxFuture<'a, Result<T, E>>
+    where
+        Context: ContextExt<Result<(), E>>,
+        D: Endpoint<Context, Output = Result<T, E>>,
+        E: From<NextAlreadyCalled> + MaybeSend + 'static,
+    {
+        Box::pin(async move {
+            self.run(&mut cx).await?;
+            endpoint.call(cx).await
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ContextExt, Next, Pipeline};
+    use futures::executor::block_on;
+
+    type Result<T = ()> = std::result::Result<T, &'static str>;
+
+    #[derive(Default)]
+    struct Context {
+        fail: bool,
+        body: Vec<&'static str>,
+        next: Next<Self, Result>,
+    }
+
+    impl ContextExt<Result> for Context {
+        fn next_mut(&mut self) -> &mut Next<Self, Result> {
+            &mut self.next
+        }
+
+        fn next_ref(&self) -> &Next<Self, Result> {
+            &self.next
+        }
+    }
+
+    async fn wrap(cx: &mut Context) -> Result {
+        cx.body.push("<");
+        let output = cx.next().await;
+        cx.body.push(">");
+        output
+    }
+
+    async fn check(cx: &mut Context) -> Result {
+        if cx.fail {
+            return Err("check");
+        }
+        cx.body.push("checked");
+        cx.next().await
+    }
+
+    async fn respond(cx: Context) -> Result<Vec<&'static str>> {
+        Ok(cx.body)
+    }
+
+    #[test]
+    fn after_the_tail() {
+        let mut pipeline = Pipeline::new();
+        pipeline.push(wrap).push(check);
+
+        let output = block_on(pipeline.run_owned(Context::default(), &respond));
+        assert_eq!(output, Ok(vec!["<", "checked", ">"]));
+
+        let cx = Context {
+            fail: true,
+            ..Default::default()
+        };
+        assert_eq!(block_on(pipeline.run_owned(cx, &respond)), Err("check"));
+    }
+}
@@ -0,0 +1,134 @@
+use alloc::sync::Arc;
+use core::fmt;
+
+use crate::{ArcHandle, BoxFuture, Handle};
+
+/// A cloneable [`Handle`] with the `'a` lifetime erased.
+///
+/// It can be stored in long-lived structs, maps and statics without spelling
+/// out the `for<'a>` bound.
+pub struct ErasedHandle<Context, Output>(ArcHandle<Context, Output>);
+
+impl<Context, Output> ErasedHandle<Context, Output> {
+    /// Creates a new [`ErasedHandle`].
+    pub fn new<H>(h: H) -> Self
+    where
+        H: for<'a> Handle<'a, Context, Output = Output>,
+    {
+        Self(Arc::new(h))
+    }
+
+    /// Invokes the handler within the given `Context` and then returns `Output`.
+    #[inline]
+    pub fn call<'a>(&'a self, cx: &'a mut Context) -> BoxFuture<'a, Output>
+    where
+        Context: 'static,
+        Output: 'static,
+    {
+        self.0.call(cx)
+    }
+
+    /// Returns the inner [`ArcHandle`].
+    #[inline]
+    pub fn into_inner(self) -> ArcHandle<Context, Output> {
+        self.0
+    }
+}
+
+impl<Context, Output> From<ArcHandle<Context, Output>> for ErasedHandle<Context, Output> {
+    #[inline]
+    fn from(h: ArcHandle<Context, Output>) -> Self {
+        Self(h)
+    }
+}
+
+impl<'a, Context, Output> Handle<'a, Context> for ErasedHandle<Context, Output>
+where
+    Context: 'static,
+    Output: 'static,
+{
+    type Output = Output;
+
+    #[inline]
+    fn call(&'a self, cx: &'a mut Context) -> BoxFuture<'a, Self::Output> {
+        self.0.call(cx)
+    }
+
+    #[inline]
+    fn name(&self) -> &str {
+        self.0.name()
+    }
+}
+
+impl<Context, Output> Clone for ErasedHandle<Context, Output> {
+    #[inline]
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+impl<Context, Output> fmt::Debug for ErasedHandle<Context, Output>
+where
+    Context: 'static,
+    Output: 'static,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("ErasedHandle").field(&self.0.name()).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ErasedHandle;
+    use futures::executor::block_on;
+    use std::collections::HashMap;
+
+    #[derive(Default)]
+    struct Context {
+        index: usize,
+    }
+
+    async fn add(cx: &mut Context) -> usize {
+        cx.index += 1;
+        cx.index
+    }
+
+    async fn double(cx: &mut Context) -> usize {
+        cx.index *= 2;
+        cx.index
+    }
+
+    struct Config {
+        routes: HashMap<&'static str, ErasedHandle<Context, usize>>,
+    }
+
+    #[test]
+    fn storable() {
+        let config = Config {
+            routes: HashMap::from([
+                ("add", ErasedHandle::new(add)),
+                ("double", ErasedHandle::new(double)),
+            ]),
+        };
+
+        let mut cx = Context::default();
+        assert_eq!(block_on(config.routes["add"].call(&mut cx)), 1);
+        assert_eq!(block_on(config.routes["double"].clone().call(&mut cx)), 2);
+    }
+
+    #[cfg(feature = "send")]
+    static FALLBACK: std::sync::OnceLock<ErasedHandle<Context, usize>> = std::sync::OnceLock::new();
+
+    #[cfg(feature = "send")]
+    fn assert_send_sync<T: Send + Sync + Clone>() {}
+
+    #[test]
+    #[cfg(feature = "send")]
+    fn static_storable() {
+        assert_send_sync::<ErasedHandle<Context, usize>>();
+
+        let fallback = FALLBACK.get_or_init(|| ErasedHandle::new(add));
+        let mut cx = Context::default();
+        assert_eq!(block_on(fallback.call(&mut cx)), 1);
+    }
+}
@@ -0,0 +1,180 @@
+use alloc::boxed::Box;
+use core::{error::Error, fmt, marker::PhantomData};
+
+use crate::{BoxFuture, Handle, MaybeSend, MaybeSync};
+
+/// Decides whether an [`ErrorHandle`] recovers from an error.
+pub trait ErrorPredicate<E>: MaybeSend + MaybeSync + 'static {
+    /// Returns `true` if the error should be recovered.
+    fn matches(&self, e: &E) -> bool;
+}
+
+impl<E, F> ErrorPredicate<E> for F
+where
+    F: Fn(&E) -> bool + MaybeSend + MaybeSync + 'static,
+{
+    #[inline]
+    fn matches(&self, e: &E) -> bool {
+        (self)(e)
+    }
+}
+
+/// Matches the errors which downcast to `C`, see [`match_error`].
+pub struct MatchError<C>(PhantomData<fn() -> C>);
+
+impl<C> Clone for MatchError<C> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<C> Copy for MatchError<C> {}
+
+impl<C> fmt::Debug for MatchError<C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("MatchError")
+            .field(&core::any::type_name::<C>())
+            .finish()
+    }
+}
+
+/// Creates a predicate matching the errors which downcast to `C`.
+///
+/// It works with dynamic errors such as `Box<dyn Error + Send + Sync>` or
+/// `anyhow::Error`.
+#[inline]
+pub fn match_error<C>() -> MatchError<C> {
+    MatchError(PhantomData)
+}
+
+impl<E, C> ErrorPredicate<E> for MatchError<C>
+where
+    E: AsRef<dyn Error + Send + Sync + 'static>,
+    C: Error + 'static,
+{
+    #[inline]
+    fn matches(&self, e: &E) -> bool {
+        e.as_ref().downcast_ref::<C>().is_some()
+    }
+}
+
+/// Calls the recovery handler when the handler fails with a matching error.
+///
+/// Other errors and successful outputs are passed through. When several
+/// [`ErrorHandle`]s are stacked, only the innermost matching one fires as long
+/// as its recovery succeeds.
+#[derive(Debug, Clone)]
+pub struct ErrorHandle<H, P, R> {
+    h: H,
+    pred: P,
+    recovery: R,
+}
+
+impl<H, P, R> ErrorHandle<H, P, R> {
+    /// Creates a new [`ErrorHandle`].
+    #[inline]
+    pub const fn new(h: H, pred: P, recovery: R) -> Self {
+        Self { h, pred, recovery }
+    }
+}
+
+impl<'a, Context, H, P, R, T, E> Handle<'a, Context> for ErrorHandle<H, P, R>
+where
+    H: for<'b> Handle<'b, Context, Output = Result<T, E>>,
+    P: ErrorPredicate<E>,
+    R: Handle<'a, Context, Output = Result<T, E>>,
+    Context: MaybeSend + 'a,
+    T: MaybeSend + 'a,
+    E: MaybeSend + 'a,
+{
+    type Output = Result<T, E>;
+
+    fn call(&'a self, cx: &'a mut Context) -> BoxFuture<'a, Self::Output> {
+        Box::pin(async move {
+            match self.h.call(&mut *cx).await {
+                Err(e) if self.pred.matches(&e) => self.recovery.call(cx).await,
+                output => output,
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{match_error, Handle, HandleExt};
+    use futures::executor::block_on;
+    use std::{error::Error, fmt, io};
+
+    type BoxError = Box<dyn Error + Send + Sync>;
+    type Result = std::result::Result<(), BoxError>;
+
+    #[derive(Debug)]
+    struct NotFound;
+
+    impl fmt::Display for NotFound {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.write_str("not found")
+        }
+    }
+
+    impl Error for NotFound {}
+
+    #[derive(Default)]
+    struct Context {
+        fail: Option<fn() -> BoxError>,
+        trace: Vec<&'static str>,
+    }
+
+    async fn handler(cx: &mut Context) -> Result {
+        cx.trace.push("handler");
+        cx.fail.map_or(Ok(()), |f| Err(f()))
+    }
+
+    async fn not_found(cx: &mut Context) -> Result {
+        cx.trace.push("not_found");
+        Ok(())
+    }
+
+    async fn any(cx: &mut Context) -> Result {
+        cx.trace.push("any");
+        Ok(())
+    }
+
+    fn run(fail: Option<fn() -> BoxError>) -> (bool, Vec<&'static str>) {
+        let h = handler
+            .on_error(match_error::<NotFound>(), not_found)
+            .on_error(|_: &BoxError| true, any);
+
+        let mut cx = Context {
+            fail,
+            ..Default::default()
+        };
+        let ok = block_on(h.call(&mut cx)).is_ok();
+        (ok, cx.trace)
+    }
+
+    #[test]
+    fn first_match_fires() {
+        assert_eq!(run(None), (true, vec!["handler"]));
+        assert_eq!(
+            run(Some(|| NotFound.into())),
+            (true, vec!["handler", "not_found"])
+        );
+        assert_eq!(
+            run(Some(|| io::Error::other("io").into())),
+            (true, vec!["handler", "any"])
+        );
+    }
+
+    #[test]
+    fn passes_through() {
+        let h = handler.on_error(match_error::<NotFound>(), not_found);
+
+        let mut cx = Context {
+            fail: Some(|| io::Error::other("io").into()),
+            ..Default::default()
+        };
+        assert!(block_on(h.call(&mut cx)).is_err());
+        assert_eq!(cx.trace, ["handler"]);
+    }
+}
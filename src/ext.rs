@@ -0,0 +1,117 @@
+use crate::{
+    Catch, Chain, Coerce, ErrorHandle, Fallback, Handle, HandlerMeta, NamedHandle, Ordered,
+    Snapshot, Take, UntilBreak,
+};
+
+/// A extension trait for [`Handle`]s that provides a variety of convenient adapters.
+pub trait HandleExt<Context>: Sized
+where
+    Self: for<'a> Handle<'a, Context>,
+{
+    /// Catches the error returned by the handler and recovers it with the `f`.
+    ///
+    /// The `f` receives the context after the handler has completed, so it can
+    /// write a response into the context. Its output is the output of the
+    /// returned handler, into which the `Ok` of the handler is converted.
+    fn catch<F>(self, f: F) -> Catch<Self, F> {
+        Catch::new(self, f)
+    }
+
+    /// Runs the handler `h` after this one when it succeeds, see [`Chain`].
+    ///
+    /// The returned chain is extended with `>>`.
+    fn chain<H>(self, h: H) -> Chain<Self, H> {
+        Chain::new(self, h)
+    }
+
+    /// Converts the output of the handler into `Output` once it completes, see
+    /// [`coerce`](crate::coerce).
+    fn coerce<Output>(self) -> Coerce<Self, Output> {
+        Coerce::new(self)
+    }
+
+    /// Calls the `fallback` handler with the context when the handler returns
+    /// an error, the error itself is dropped.
+    ///
+    /// The context mutations made by the handler before it failed are seen by
+    /// the `fallback`.
+    fn fallback<F>(self, fallback: F) -> Fallback<Self, F> {
+        Fallback::new(self, fallback)
+    }
+
+    /// Logs each call of the handler with the [`log`] crate, named by its
+    /// type, see [`LogHandle`](crate::LogHandle).
+    #[cfg(feature = "log")]
+    fn logged(self) -> crate::LogHandle<Self> {
+        crate::LogHandle::new(self, core::any::type_name::<Self>())
+    }
+
+    /// Names the handler, overriding [`Handle::name`].
+    fn named(self, name: &'static str) -> NamedHandle<Self> {
+        NamedHandle::new(name, self)
+    }
+
+    /// Calls the `recovery` handler when the handler fails with an error
+    /// matching the `pred`, see [`match_error`](crate::match_error).
+    fn on_error<P, R>(self, pred: P, recovery: R) -> ErrorHandle<Self, P, R> {
+        ErrorHandle::new(self, pred, recovery)
+    }
+
+    /// Calls the handler for the first call only, then skips it, see
+    /// [`Take`].
+    fn once(self) -> Take<Self> {
+        Take::new(self, 1)
+    }
+
+    /// Attaches the [`HandlerMeta`] of the handler, so [`validate_pipeline`]
+    /// checks its constraints, see [`Ordered`].
+    ///
+    /// [`validate_pipeline`]: crate::validate_pipeline
+    fn ordered<Output>(self) -> Ordered<Context, Output>
+    where
+        Self: for<'a> Handle<'a, Context, Output = Output> + HandlerMeta,
+        Context: 'static,
+        Output: 'static,
+    {
+        Ordered::new(self)
+    }
+
+    /// Restores the context to a clone taken before the call when the handler
+    /// fails, see [`Snapshot`].
+    fn snapshot(self) -> Snapshot<Self> {
+        Snapshot::new(self)
+    }
+
+    /// Calls the handler for the first `n` calls only, then skips it, see
+    /// [`Take`].
+    fn take(self, n: usize) -> Take<Self> {
+        Take::new(self, n)
+    }
+
+    /// Reports the durations of each call, named `name`, into the `sink`, see
+    /// [`Timed`](crate::Timed).
+    #[cfg(feature = "std")]
+    fn timed<S>(self, name: &'static str, sink: S) -> crate::Timed<Self, S> {
+        crate::Timed::new(self, name, sink)
+    }
+
+    /// Instruments the handler with a new span named `name` for each call.
+    ///
+    /// The span is created inside the current span, so nested handlers form a
+    /// span tree matching the pipeline. The handler must return a
+    /// `Result<T, E>`, whose variant is recorded, see
+    /// [`Instrumented`](crate::Instrumented).
+    #[cfg(feature = "tracing")]
+    fn traced(self, name: &'static str) -> crate::Instrumented<Self> {
+        crate::Instrumented::new(self, name)
+    }
+
+    /// Continues the pipeline after the handler while it returns
+    /// [`ControlFlow::Continue`](core::ops::ControlFlow) or `Ok`, see
+    /// [`UntilBreak`].
+    fn until_break(self) -> UntilBreak<Self> {
+        UntilBreak::new(self)
+    }
+}
+
+impl<Context, H> HandleExt<Context> for H where H: for<'a> Handle<'a, Context> {}
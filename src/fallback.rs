@@ -0,0 +1,207 @@
+use crate::{ArcHandle, BoxFuture, Handle, MaybeSend};
+use alloc::{boxed::Box, vec::Vec};
+use core::fmt;
+
+/// Calls the fallback handler when the handler returns an error.
+///
+/// The fallback runs on the cursor of the pipeline, if the handler failed
+/// after calling [`ContextExt::next`](crate::ContextExt::next), the fallback
+/// gets the [`NextAlreadyCalled`](crate::NextAlreadyCalled) error from it.
+#[derive(Debug, Clone)]
+pub struct Fallback<H, F> {
+    h: H,
+    fallback: F,
+}
+
+impl<H, F> Fallback<H, F> {
+    /// Creates a new [`Fallback`].
+    #[inline]
+    pub const fn new(h: H, fallback: F) -> Self {
+        Self { h, fallback }
+    }
+}
+
+impl<'a, Context, H, F, T, E> Handle<'a, Context> for Fallback<H, F>
+where
+    H: for<'b> Handle<'b, Context, Output = Result<T, E>>,
+    F: Handle<'a, Context, Output = Result<T, E>>,
+    Context: MaybeSend + 'a,
+    T: MaybeSend + 'a,
+    E: MaybeSend + 'a,
+{
+    type Output = Result<T, E>;
+
+    fn call(&'a self, cx: &'a mut Context) -> BoxFuture<'a, Self::Output> {
+        Box::pin(async move {
+            // Reborrows the context, it is released when the inner future completes.
+            match self.h.call(&mut *cx).await {
+                Ok(t) => Ok(t),
+                Err(_) => self.fallback.call(cx).await,
+            }
+        })
+    }
+}
+
+/// Tries the handlers in order until one of them returns `Ok`.
+///
+/// When all of them fail, the errors are returned in the order of the
+/// handlers. They share the cursor of the pipeline, so only the first one to
+/// call [`ContextExt::next`](crate::ContextExt::next) runs the rest of it, the
+/// others get the [`NextAlreadyCalled`](crate::NextAlreadyCalled) error.
+pub struct FallbackChain<Context, T, E> {
+    handlers: Vec<ArcHandle<Context, Result<T, E>>>,
+}
+
+impl<Context, T, E> FallbackChain<Context, T, E> {
+    /// Creates a new [`FallbackChain`].
+    #[inline]
+    pub fn new(handlers: Vec<ArcHandle<Context, Result<T, E>>>) -> Self {
+        Self { handlers }
+    }
+}
+
+impl<Context, T, E> Clone for FallbackChain<Context, T, E> {
+    fn clone(&self) -> Self {
+        Self::new(self.handlers.clone())
+    }
+}
+
+impl<Context, T, E> fmt::Debug for FallbackChain<Context, T, E>
+where
+    Context: 'static,
+    T: 'static,
+    E: 'static,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FallbackChain")
+            .field("handlers", &self.handlers)
+            .finish()
+    }
+}
+
+impl<'a, Context, T, E> Handle<'a, Context> for FallbackChain<Context, T, E>
+where
+    Context: MaybeSend + 'static,
+    T: MaybeSend + 'static,
+    E: MaybeSend + 'static,
+{
+    type Output = Result<T, Vec<E>>;
+
+    fn call(&'a self, cx: &'a mut Context) -> BoxFuture<'a, Self::Output> {
+        Box::pin(async move {
+            let mut errors = Vec::with_capacity(self.handlers.len());
+            for h in &self.handlers {
+                // Each attempt reborrows the context until its future completes.
+                match h.call(&mut *cx).await {
+                    Ok(t) => return Ok(t),
+                    Err(e) => errors.push(e),
+                }
+            }
+            Err(errors)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ArcHandle, FallbackChain, Handle, HandleExt};
+    use anyhow::{anyhow, Result};
+    use futures::executor::block_on;
+    use std::sync::Arc;
+
+    #[derive(Default)]
+    struct Context {
+        trace: Vec<&'static str>,
+        hit: bool,
+    }
+
+    #[derive(Default)]
+    struct Lookup {
+        trace: Vec<&'static str>,
+        found: Option<&'static str>,
+    }
+
+    fn strategy(name: &'static str) -> ArcHandle<Lookup, Result<&'static str, &'static str>> {
+        Arc::new(move |cx: &mut Lookup| {
+            cx.trace.push(name);
+            let found = cx.found == Some(name);
+            async move {
+                if found {
+                    Ok(name)
+                } else {
+                    Err(name)
+                }
+            }
+        })
+    }
+
+    fn resolve() -> FallbackChain<Lookup, &'static str, &'static str> {
+        FallbackChain::new(vec![strategy("cache"), strategy("db"), strategy("remote")])
+    }
+
+    async fn cache(cx: &mut Context) -> Result<&'static str> {
+        cx.trace.push("cache");
+        if cx.hit {
+            Ok("cached")
+        } else {
+            Err(anyhow!("miss"))
+        }
+    }
+
+    async fn db(cx: &mut Context) -> Result<&'static str> {
+        cx.trace.push("db");
+        Ok("fresh")
+    }
+
+    #[test]
+    fn fallback_err() {
+        let mut cx = Context::default();
+        let h = cache.fallback(db);
+
+        assert_eq!(block_on(h.call(&mut cx)).unwrap(), "fresh");
+        // The mutations made before the error are seen by the fallback.
+        assert_eq!(cx.trace, ["cache", "db"]);
+    }
+
+    #[test]
+    fn fallback_ok() {
+        let mut cx = Context {
+            hit: true,
+            ..Default::default()
+        };
+        let h = cache.fallback(db);
+
+        assert_eq!(block_on(h.call(&mut cx)).unwrap(), "cached");
+        assert_eq!(cx.trace, ["cache"]);
+    }
+
+    #[test]
+    fn chain_first() {
+        let mut cx = Lookup {
+            found: Some("cache"),
+            ..Default::default()
+        };
+        assert_eq!(block_on(resolve().call(&mut cx)), Ok("cache"));
+        assert_eq!(cx.trace, ["cache"]);
+    }
+
+    #[test]
+    fn chain_middle() {
+        let mut cx = Lookup {
+            found: Some("db"),
+            ..Default::default()
+        };
+        assert_eq!(block_on(resolve().call(&mut cx)), Ok("db"));
+        assert_eq!(cx.trace, ["cache", "db"]);
+    }
+
+    #[test]
+    fn chain_all_fail() {
+        let mut cx = Lookup::default();
+        assert_eq!(
+            block_on(resolve().call(&mut cx)),
+            Err(vec!["cache", "db", "remote"])
+        );
+        assert_eq!(cx.trace, ["cache", "db", "remote"]);
+    }
+}
@@ -0,0 +1,229 @@
+use alloc::vec::Vec;
+use core::{
+    future::{poll_fn, Future},
+    task::Poll,
+};
+
+use crate::{join::join_all, BoxFuture, ErasedHandle, MaybeSend};
+
+/// Runs each handler concurrently on its own clone of the context, and returns
+/// their outputs in the order of the `handlers`.
+///
+/// The clones are dropped once all the handlers complete, so no handler sees
+/// the mutations of another one. Use [`fanout_merge`] to keep them.
+pub fn fanout<Context, Output>(
+    cx: Context,
+    handlers: Vec<ErasedHandle<Context, Output>>,
+) -> impl Future<Output = Vec<Output>> + MaybeSend
+where
+    Context: Clone + MaybeSend + 'static,
+    Output: MaybeSend + 'static,
+{
+    let clones = clone_for(&cx, &handlers);
+    async move { run(clones, &handlers).await.1 }
+}
+
+/// Runs each handler concurrently on its own clone of the context, then
+/// reconciles the clones into a single context with `merge`.
+///
+/// The clones are passed to `merge` in the order of the `handlers`, and the
+/// merged context is returned with the outputs.
+pub fn fanout_merge<Context, Output, M>(
+    cx: Context,
+    handlers: Vec<ErasedHandle<Context, Output>>,
+    merge: M,
+) -> impl Future<Output = (Context, Vec<Output>)> + MaybeSend
+where
+    Context: Clone + MaybeSend + 'static,
+    Output: MaybeSend + 'static,
+    M: FnOnce(Vec<Context>) -> Context + MaybeSend,
+{
+    let clones = clone_for(&cx, &handlers);
+    async move {
+        let (clones, outputs) = run(clones, &handlers).await;
+        (merge(clones), outputs)
+    }
+}
+
+/// Runs each handler concurrently on its own clone of the context, and returns
+/// the first `Ok`.
+///
+/// The other handlers are dropped as soon as one of them succeeds. When all of
+/// them fail, their errors are returned in the order of the `handlers`, so an
+/// empty list of handlers returns no errors.
+pub fn select_ok<Context, T, E>(
+    cx: Context,
+    handlers: Vec<ErasedHandle<Context, Result<T, E>>>,
+) -> impl Future<Output = Result<T, Vec<E>>> + MaybeSend
+where
+    Context: Clone + MaybeSend + 'static,
+    T: MaybeSend + 'static,
+    E: MaybeSend + 'static,
+{
+    let mut clones = clone_for(&cx, &handlers);
+    async move {
+        let mut futures: Vec<Option<BoxFuture<'_, Result<T, E>>>> = handlers
+            .iter()
+            .zip(clones.iter_mut())
+            .map(|(h, cx)| Some(h.call(cx)))
+            .collect();
+        let mut errors: Vec<Option<E>> = futures.iter().map(|_| None).collect();
+
+        poll_fn(|task| {
+            for (slot, error) in futures.iter_mut().zip(errors.iter_mut()) {
+                if let Some(fut) = slot {
+                    match fut.as_mut().poll(task) {
+                        Poll::Ready(Ok(t)) => return Poll::Ready(Ok(t)),
+                        Poll::Ready(Err(e)) => {
+                            *error = Some(e);
+                            *slot = None;
+                        }
+                        Poll::Pending => {}
+                    }
+                }
+            }
+            if futures.iter().all(Option::is_none) {
+                Poll::Ready(Err(errors.iter_mut().filter_map(Option::take).collect()))
+            } else {
+                Poll::Pending
+            }
+        })
+        .await
+    }
+}
+
+fn clone_for<Context, Output>(
+    cx: &Context,
+    handlers: &[ErasedHandle<Context, Output>],
+) -> Vec<Context>
+where
+    Context: Clone,
+{
+    handlers.iter().map(|_| cx.clone()).collect()
+}
+
+async fn run<Context, Output>(
+    mut clones: Vec<Context>,
+    handlers: &[ErasedHandle<Context, Output>],
+) -> (Vec<Context>, Vec<Output>)
+where
+    Context: MaybeSend + 'static,
+    Output: MaybeSend + 'static,
+{
+    let outputs = join_all(
+        handlers
+            .iter()
+            .zip(clones.iter_mut())
+            .map(|(h, cx)| h.call(cx))
+            .collect(),
+    )
+    .await;
+    (clones, outputs)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{fanout, fanout_merge, select_ok, ErasedHandle};
+    use async_std::task::sleep;
+    use futures::{executor::block_on, future::pending};
+    use std::{
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc,
+        },
+        time::Duration,
+    };
+
+    #[derive(Clone, Default)]
+    struct Context {
+        trace: Vec<&'static str>,
+    }
+
+    async fn a(cx: &mut Context) -> usize {
+        cx.trace.push("a");
+        cx.trace.len()
+    }
+
+    async fn b(cx: &mut Context) -> usize {
+        cx.trace.push("b");
+        cx.trace.push("b");
+        cx.trace.len()
+    }
+
+    fn handlers() -> Vec<ErasedHandle<Context, usize>> {
+        vec![ErasedHandle::new(b), ErasedHandle::new(a)]
+    }
+
+    #[test]
+    fn isolated_clones() {
+        let cx = Context {
+            trace: vec!["root"],
+        };
+        assert_eq!(block_on(fanout(cx, handlers())), [3, 2]);
+    }
+
+    #[test]
+    fn merge_clones() {
+        let (cx, outputs) = block_on(fanout_merge(Context::default(), handlers(), |clones| {
+            Context {
+                trace: clones.into_iter().flat_map(|cx| cx.trace).collect(),
+            }
+        }));
+        assert_eq!(outputs, [2, 1]);
+        assert_eq!(cx.trace, ["b", "b", "a"]);
+    }
+
+    #[derive(Clone, Default)]
+    struct Race {
+        dropped: Arc<AtomicUsize>,
+    }
+
+    struct Guard(Arc<AtomicUsize>);
+
+    impl Drop for Guard {
+        fn drop(&mut self) {
+            self.0.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    async fn fail_fast(_: &mut Race) -> Result<&'static str, &'static str> {
+        Err("fast")
+    }
+
+    async fn fail_slow(_: &mut Race) -> Result<&'static str, &'static str> {
+        sleep(Duration::from_millis(20)).await;
+        Err("slow")
+    }
+
+    async fn win(_: &mut Race) -> Result<&'static str, &'static str> {
+        sleep(Duration::from_millis(10)).await;
+        Ok("win")
+    }
+
+    async fn stall(cx: &mut Race) -> Result<&'static str, &'static str> {
+        let _guard = Guard(cx.dropped.clone());
+        pending().await
+    }
+
+    #[async_std::test]
+    async fn first_ok() {
+        let cx = Race::default();
+        let handlers = vec![
+            ErasedHandle::new(fail_fast),
+            ErasedHandle::new(win),
+            ErasedHandle::new(stall),
+            ErasedHandle::new(stall),
+        ];
+        assert_eq!(select_ok(cx.clone(), handlers).await, Ok("win"));
+        assert_eq!(cx.dropped.load(Ordering::Relaxed), 2);
+    }
+
+    #[async_std::test]
+    async fn all_errors() {
+        let handlers = vec![ErasedHandle::new(fail_slow), ErasedHandle::new(fail_fast)];
+        assert_eq!(
+            select_ok(Race::default(), handlers).await,
+            Err(vec!["slow", "fast"])
+        );
+    }
+}
@@ -0,0 +1,99 @@
+use std::{
+    future::Future,
+    sync::{Mutex, PoisonError},
+};
+
+use crate::{BoxFuture, Handle, MaybeSend};
+
+/// A handler calling a [`FnMut`] closure, so the closure can mutate the state
+/// it captures.
+///
+/// The closure is stored in a [`Mutex`], locked for each call until the
+/// closure returns its future, the future itself runs unlocked. Concurrent
+/// calls contend on the lock, so prefer a [`Fn`] closure updating atomics or
+/// other interior mutability on hot paths.
+#[derive(Debug)]
+pub struct FnMutHandle<F> {
+    f: Mutex<F>,
+}
+
+impl<F> FnMutHandle<F> {
+    /// Creates a new [`FnMutHandle`].
+    #[inline]
+    pub const fn new(f: F) -> Self {
+        Self { f: Mutex::new(f) }
+    }
+
+    /// Returns the inner closure.
+    pub fn into_inner(self) -> F {
+        self.f.into_inner().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl<'a, Context, F, Fut> Handle<'a, Context> for FnMutHandle<F>
+where
+    F: FnMut(&'a mut Context) -> Fut + MaybeSend + 'static,
+    Fut: Future + MaybeSend + 'a,
+    Context: 'a,
+{
+    type Output = Fut::Output;
+
+    fn call(&'a self, cx: &'a mut Context) -> BoxFuture<'a, Self::Output> {
+        let fut = (self.f.lock().unwrap_or_else(PoisonError::into_inner))(cx);
+        Box::pin(fut)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{FnMutHandle, Handle};
+    use futures::executor::block_on;
+    use std::{collections::BTreeSet, future::ready, sync::Arc, thread};
+
+    #[derive(Default)]
+    struct Context {
+        request: usize,
+    }
+
+    fn counter() -> FnMutHandle<impl FnMut(&mut Context) -> std::future::Ready<usize>> {
+        let mut requests = 0;
+        FnMutHandle::new(move |cx: &mut Context| {
+            requests += 1;
+            cx.request = requests;
+            ready(requests)
+        })
+    }
+
+    #[test]
+    fn counts_requests() {
+        let h = counter();
+
+        let mut cx = Context::default();
+        assert_eq!(block_on(h.call(&mut cx)), 1);
+        assert_eq!(block_on(h.call(&mut cx)), 2);
+        assert_eq!(cx.request, 2);
+    }
+
+    #[test]
+    fn concurrent() {
+        let h = Arc::new(counter());
+
+        let threads: Vec<_> = (0..8)
+            .map(|_| {
+                let h = h.clone();
+                thread::spawn(move || {
+                    let mut cx = Context::default();
+                    (0..100)
+                        .map(|_| block_on(h.call(&mut cx)))
+                        .collect::<Vec<_>>()
+                })
+            })
+            .collect();
+
+        let requests: BTreeSet<_> = threads
+            .into_iter()
+            .flat_map(|t| t.join().unwrap())
+            .collect();
+        assert_eq!(requests, (1..=800).collect());
+    }
+}
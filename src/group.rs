@@ -0,0 +1,206 @@
+use alloc::{sync::Arc, vec::Vec};
+use core::fmt;
+
+use crate::{
+    ArcHandle, BoxFuture, ContextExt, Empty, FromNextAlreadyCalled, Handle, IntoHandle, Next,
+};
+
+/// A named group of handlers, running as a single handler.
+///
+/// The handlers of the group run in place of the group, as if they had been
+/// pushed one by one: the last one continues with the handlers after the
+/// group. The name of the group is its [`Handle::name`], which the pipeline
+/// and the registry report instead of the names of the handlers, and which
+/// names the span when the group is wrapped in `Instrumented`.
+pub struct HandleGroup<Context, Output> {
+    name: &'static str,
+    handlers: Arc<[ArcHandle<Context, Output>]>,
+}
+
+impl<Context, Output> HandleGroup<Context, Output> {
+    /// Starts building a [`HandleGroup`] named `name`.
+    #[inline]
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new(name: &'static str) -> HandleGroupBuilder<Context, Output> {
+        HandleGroupBuilder {
+            name,
+            handlers: Vec::new(),
+        }
+    }
+
+    /// Returns the name of the group.
+    #[inline]
+    pub const fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// Returns the number of handlers in the group.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.handlers.len()
+    }
+
+    /// Returns `true` if the group has no handlers.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.handlers.is_empty()
+    }
+}
+
+impl<'a, Context, Output> Handle<'a, Context> for HandleGroup<Context, Output>
+where
+    Context: ContextExt<Output>,
+    Output: Empty + FromNextAlreadyCalled + 'static,
+{
+    type Output = Output;
+
+    fn call(&'a self, cx: &'a mut Context) -> BoxFuture<'a, Self::Output> {
+        // The handlers after the group run from the cursor of the group, so
+        // they are skipped in the outer cursor, which is restored afterwards.
+        let outer = cx.next_mut();
+        let handlers = self.handlers.iter().chain(outer.upcoming()).cloned();
+        let next = Next::new(handlers.collect());
+        outer.skip(outer.remaining());
+        next.run_nested(cx)
+    }
+
+    #[inline]
+    fn name(&self) -> &str {
+        self.name
+    }
+}
+
+impl<Context, Output> Clone for HandleGroup<Context, Output> {
+    fn clone(&self) -> Self {
+        Self {
+            name: self.name,
+            handlers: self.handlers.clone(),
+        }
+    }
+}
+
+impl<Context, Output> fmt::Debug for HandleGroup<Context, Output>
+where
+    Context: 'static,
+    Output: 'static,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("HandleGroup")
+            .field("name", &self.name)
+            .field("handlers", &self.handlers)
+            .finish()
+    }
+}
+
+/// The builder of a [`HandleGroup`], see [`HandleGroup::new`].
+pub struct HandleGroupBuilder<Context, Output> {
+    name: &'static str,
+    handlers: Vec<ArcHandle<Context, Output>>,
+}
+
+impl<Context, Output> HandleGroupBuilder<Context, Output> {
+    /// Appends a handler to the group, which can be another group.
+    #[must_use]
+    pub fn push<H, K>(mut self, h: H) -> Self
+    where
+        H: IntoHandle<Context, Output, K>,
+    {
+        self.handlers.push(h.into_handle());
+        self
+    }
+
+    /// Builds the [`HandleGroup`].
+    #[inline]
+    pub fn build(self) -> HandleGroup<Context, Output> {
+        HandleGroup {
+            name: self.name,
+            handlers: self.handlers.into(),
+        }
+    }
+}
+
+impl<Context, Output> fmt::Debug for HandleGroupBuilder<Context, Output>
+where
+    Context: 'static,
+    Output: 'static,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("HandleGroupBuilder")
+            .field("name", &self.name)
+            .field("handlers", &self.handlers)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{BoxFuture, ContextExt, HandleGroup, HandlerRegistry, Next, Pipeline};
+    use futures::executor::block_on;
+
+    type Result = anyhow::Result<()>;
+
+    #[derive(Default)]
+    struct Context {
+        trace: Vec<&'static str>,
+        next: Next<Self, Result>,
+    }
+
+    impl ContextExt<Result> for Context {
+        fn next_mut(&mut self) -> &mut Next<Self, Result> {
+            &mut self.next
+        }
+
+        fn next_ref(&self) -> &Next<Self, Result> {
+            &self.next
+        }
+    }
+
+    fn step(name: &'static str) -> impl Fn(&mut Context) -> BoxFuture<'_, Result> + Send + Sync {
+        move |cx| {
+            Box::pin(async move {
+                cx.trace.push(name);
+                cx.next().await
+            })
+        }
+    }
+
+    fn auth() -> HandleGroup<Context, Result> {
+        let session = HandleGroup::new("session")
+            .push(step("cookie"))
+            .push(step("session"))
+            .build();
+        HandleGroup::new("auth")
+            .push(session)
+            .push(step("user"))
+            .build()
+    }
+
+    #[test]
+    fn runs_in_place() {
+        let mut pipeline = Pipeline::new();
+        pipeline
+            .push(step("log"))
+            .add_group(auth())
+            .push(step("route"));
+        assert_eq!(pipeline.names()[1], "auth");
+
+        let mut cx = Context::default();
+        assert!(block_on(pipeline.run(&mut cx)).is_ok());
+        assert_eq!(cx.trace, ["log", "cookie", "session", "user", "route"]);
+    }
+
+    #[test]
+    fn replaced_at_once() {
+        let registry = HandlerRegistry::new();
+        registry.register("log", step("log"));
+        registry.register("auth", auth());
+        registry.register("route", step("route"));
+
+        let mock = HandleGroup::new("auth").push(step("mock")).build();
+        assert!(registry.register(mock.name(), mock).is_some());
+
+        let mut cx = Context::default();
+        assert!(block_on(registry.build_pipeline().run(&mut cx)).is_ok());
+        assert_eq!(cx.trace, ["log", "mock", "route"]);
+    }
+}
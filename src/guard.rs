@@ -0,0 +1,269 @@
+//! Predicates gating whether a handler and the rest of the pipeline run.
+//!
+//! A [`Guard`] only observes the context. Combine guards with the methods of
+//! [`GuardExt`], and put them in front of a handler with [`guarded`].
+
+use alloc::boxed::Box;
+use core::{error::Error, fmt, future::Future};
+
+use crate::{BoxFuture, Handle, MaybeSend, MaybeSync};
+
+/// The error returned when a [`Guard`] denies the context.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Denied;
+
+impl fmt::Display for Denied {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("denied by guard")
+    }
+}
+
+impl Error for Denied {}
+
+/// A predicate admitting or denying the context.
+pub trait Guard<'a, Context>: MaybeSend + MaybeSync + 'static {
+    /// Checks the `Context`, returns [`Denied`] when it is not admitted.
+    fn check(&'a self, cx: &'a Context) -> BoxFuture<'a, Result<(), Denied>>;
+}
+
+impl<'a, Context, F, Fut> Guard<'a, Context> for F
+where
+    F: Fn(&'a Context) -> Fut + MaybeSend + MaybeSync + 'static,
+    Fut: Future<Output = Result<(), Denied>> + MaybeSend + 'a,
+    Context: 'a,
+{
+    fn check(&'a self, cx: &'a Context) -> BoxFuture<'a, Result<(), Denied>> {
+        Box::pin((self)(cx))
+    }
+}
+
+/// An extension trait for [`Guard`]s that provides the logical combinators.
+pub trait GuardExt<Context>: Sized
+where
+    Self: for<'a> Guard<'a, Context>,
+{
+    /// Admits the context when both guards do, `other` is only checked when
+    /// this guard admits it.
+    fn and<G>(self, other: G) -> And<Self, G> {
+        And(self, other)
+    }
+
+    /// Admits the context when either guard does, `other` is only checked
+    /// when this guard denies it.
+    fn or<G>(self, other: G) -> Or<Self, G> {
+        Or(self, other)
+    }
+
+    /// Admits the context when this guard denies it.
+    fn not(self) -> Not<Self> {
+        Not(self)
+    }
+}
+
+impl<Context, G> GuardExt<Context> for G where G: for<'a> Guard<'a, Context> {}
+
+/// The guard returned by [`GuardExt::and`].
+#[derive(Debug, Clone)]
+pub struct And<A, B>(A, B);
+
+impl<'a, Context, A, B> Guard<'a, Context> for And<A, B>
+where
+    A: Guard<'a, Context>,
+    B: Guard<'a, Context>,
+    Context: MaybeSync,
+{
+    fn check(&'a self, cx: &'a Context) -> BoxFuture<'a, Result<(), Denied>> {
+        let a = self.0.check(cx);
+        Box::pin(async move {
+            a.await?;
+            self.1.check(cx).await
+        })
+    }
+}
+
+/// The guard returned by [`GuardExt::or`].
+#[derive(Debug, Clone)]
+pub struct Or<A, B>(A, B);
+
+impl<'a, Context, A, B> Guard<'a, Context> for Or<A, B>
+where
+    A: Guard<'a, Context>,
+    B: Guard<'a, Context>,
+    Context: MaybeSync,
+{
+    fn check(&'a self, cx: &'a Context) -> BoxFuture<'a, Result<(), Denied>> {
+        let a = self.0.check(cx);
+        Box::pin(async move {
+            match a.await {
+                Ok(()) => Ok(()),
+                Err(Denied) => self.1.check(cx).await,
+            }
+        })
+    }
+}
+
+/// The guard returned by [`GuardExt::not`].
+#[derive(Debug, Clone)]
+pub struct Not<G>(G);
+
+impl<'a, Context, G> Guard<'a, Context> for Not<G>
+where
+    G: Guard<'a, Context>,
+{
+    fn check(&'a self, cx: &'a Context) -> BoxFuture<'a, Result<(), Denied>> {
+        let g = self.0.check(cx);
+        Box::pin(async move {
+            match g.await {
+                Ok(()) => Err(Denied),
+                Err(Denied) => Ok(()),
+            }
+        })
+    }
+}
+
+/// Calls the handler `h` only when the `guard` admits the context, otherwise
+/// returns the [`Denied`] error converted into the error of the handler.
+#[inline]
+pub const fn guarded<G, H>(guard: G, h: H) -> Guarded<G, H> {
+    Guarded { guard, h }
+}
+
+/// The handler returned by [`guarded`].
+#[derive(Debug, Clone)]
+pub struct Guarded<G, H> {
+    guard: G,
+    h: H,
+}
+
+impl<'a, Context, G, H, T, E> Handle<'a, Context> for Guarded<G, H>
+where
+    G: for<'b> Guard<'b, Context>,
+    H: Handle<'a, Context, Output = Result<T, E>>,
+    E: From<Denied> + 'a,
+    Context: MaybeSend + MaybeSync + 'a,
+    T: 'a,
+{
+    type Output = Result<T, E>;
+
+    fn call(&'a self, cx: &'a mut Context) -> BoxFuture<'a, Self::Output> {
+        Box::pin(async move {
+            self.guard.check(&*cx).await?;
+            self.h.call(cx).await
+        })
+    }
+
+    #[inline]
+    fn name(&self) -> &str {
+        self.h.name()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{guarded, Denied, GuardExt};
+    use crate::{ContextExt, Handle, Next, NextAlreadyCalled, Pipeline};
+    use futures::executor::block_on;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[derive(Debug, PartialEq)]
+    enum Error {
+        Denied,
+        NextAlreadyCalled,
+    }
+
+    impl From<Denied> for Error {
+        fn from(_: Denied) -> Self {
+            Self::Denied
+        }
+    }
+
+    impl From<NextAlreadyCalled> for Error {
+        fn from(_: NextAlreadyCalled) -> Self {
+            Self::NextAlreadyCalled
+        }
+    }
+
+    type Result = std::result::Result<(), Error>;
+
+    #[derive(Default)]
+    struct Context {
+        user: Option<&'static str>,
+        banned: bool,
+        checks: AtomicUsize,
+        served: bool,
+        next: Next<Self, Result>,
+    }
+
+    impl ContextExt<Result> for Context {
+        fn next_mut(&mut self) -> &mut Next<Self, Result> {
+            &mut self.next
+        }
+
+        fn next_ref(&self) -> &Next<Self, Result> {
+            &self.next
+        }
+    }
+
+    async fn authenticated(cx: &Context) -> std::result::Result<(), Denied> {
+        cx.checks.fetch_add(1, Ordering::Relaxed);
+        cx.user.map(drop).ok_or(Denied)
+    }
+
+    async fn banned(cx: &Context) -> std::result::Result<(), Denied> {
+        cx.checks.fetch_add(1, Ordering::Relaxed);
+        if cx.banned {
+            Ok(())
+        } else {
+            Err(Denied)
+        }
+    }
+
+    async fn serve(cx: &mut Context) -> Result {
+        cx.served = true;
+        cx.next().await
+    }
+
+    fn pipeline() -> Pipeline<Context, Result> {
+        let mut pipeline = Pipeline::new();
+        pipeline.push(guarded(authenticated.and(banned.not()), serve));
+        pipeline
+    }
+
+    #[test]
+    fn admitted() {
+        let mut cx = Context {
+            user: Some("alice"),
+            ..Default::default()
+        };
+        assert_eq!(block_on(pipeline().run(&mut cx)), Ok(()));
+        assert_eq!(*cx.checks.get_mut(), 2);
+        assert!(cx.served);
+    }
+
+    #[test]
+    fn short_circuit() {
+        let mut cx = Context::default();
+        assert_eq!(block_on(pipeline().run(&mut cx)), Err(Error::Denied));
+        assert_eq!(*cx.checks.get_mut(), 1);
+        assert!(!cx.served);
+
+        let mut cx = Context {
+            user: Some("mallory"),
+            banned: true,
+            ..Default::default()
+        };
+        assert_eq!(block_on(pipeline().run(&mut cx)), Err(Error::Denied));
+        assert_eq!(*cx.checks.get_mut(), 2);
+    }
+
+    #[test]
+    fn or() {
+        let mut cx = Context {
+            banned: true,
+            ..Default::default()
+        };
+        let guard = authenticated.or(banned);
+        assert_eq!(block_on(guarded(guard, serve).call(&mut cx)), Ok(()));
+        assert_eq!(*cx.checks.get_mut(), 2);
+    }
+}
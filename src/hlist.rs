@@ -0,0 +1,185 @@
+use alloc::boxed::Box;
+use core::{fmt, marker::PhantomData};
+
+use crate::{BoxFuture, Empty, Handle, MaybeSend};
+
+/// The end of a statically dispatched pipeline, see [`hpipeline!`].
+///
+/// It returns [`Empty::empty`] without touching the context. The `Output`
+/// parameter is the output of the pipeline, it is inferred from the handlers.
+pub struct HNil<Output>(PhantomData<fn() -> Output>);
+
+impl<Output> HNil<Output> {
+    /// Creates a new [`HNil`].
+    #[inline]
+    pub const fn new() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<Output> Default for HNil<Output> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<Output> Clone for HNil<Output> {
+    #[inline]
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<Output> Copy for HNil<Output> {}
+
+impl<Output> fmt::Debug for HNil<Output> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("HNil")
+    }
+}
+
+impl<'a, Context, Output> Handle<'a, Context> for HNil<Output>
+where
+    Output: Empty + MaybeSend + 'static,
+{
+    type Output = Output;
+
+    #[inline]
+    fn call(&'a self, _: &'a mut Context) -> BoxFuture<'a, Self::Output> {
+        Box::pin(async { Output::empty() })
+    }
+}
+
+/// A statically dispatched pipeline of the handler `H` followed by `T`, see
+/// [`hpipeline!`].
+///
+/// The head runs first and the tail only if it succeeds, the output is the
+/// tail's. The handlers are called directly, without any [`ArcHandle`]
+/// indirection or handler storage, so the whole chain is monomorphized.
+///
+/// The handlers share the cursor of the enclosing pipeline, only the first one
+/// calling [`ContextExt::next`] runs the rest of it, the following ones get
+/// the [`NextAlreadyCalled`] error.
+///
+/// [`ArcHandle`]: crate::ArcHandle
+/// [`ContextExt::next`]: crate::ContextExt::next
+/// [`NextAlreadyCalled`]: crate::NextAlreadyCalled
+#[derive(Debug, Clone, Copy, Default)]
+pub struct HCons<H, T>(pub H, pub T);
+
+impl<H, T> HCons<H, T> {
+    /// Creates a new [`HCons`].
+    #[inline]
+    pub const fn new(h: H, t: T) -> Self {
+        Self(h, t)
+    }
+}
+
+impl<'a, Context, H, T, O, E> Handle<'a, Context> for HCons<H, T>
+where
+    H: for<'b> Handle<'b, Context, Output = Result<O, E>>,
+    T: Handle<'a, Context, Output = Result<O, E>>,
+    Context: MaybeSend + 'a,
+    O: MaybeSend + 'a,
+    E: MaybeSend + 'a,
+{
+    type Output = Result<O, E>;
+
+    fn call(&'a self, cx: &'a mut Context) -> BoxFuture<'a, Self::Output> {
+        Box::pin(async move {
+            self.0.call(&mut *cx).await?;
+            self.1.call(cx).await
+        })
+    }
+}
+
+/// Builds a statically dispatched pipeline from a list of handlers.
+///
+/// `hpipeline!(a, b, c)` expands to
+/// `HCons::new(a, HCons::new(b, HCons::new(c, HNil::new())))`.
+///
+/// ```
+/// use futures::executor::block_on;
+/// use handle::{hpipeline, Handle};
+///
+/// async fn a(cx: &mut Vec<&'static str>) -> Result<(), ()> {
+///     cx.push("a");
+///     Ok(())
+/// }
+///
+/// async fn b(cx: &mut Vec<&'static str>) -> Result<(), ()> {
+///     cx.push("b");
+///     Ok(())
+/// }
+///
+/// let mut cx = Vec::new();
+/// assert!(block_on(hpipeline!(a, b).call(&mut cx)).is_ok());
+/// assert_eq!(cx, ["a", "b"]);
+/// ```
+#[macro_export]
+macro_rules! hpipeline {
+    () => {
+        $crate::HNil::new()
+    };
+    ($h:expr $(, $t:expr)* $(,)?) => {
+        $crate::HCons::new($h, $crate::hpipeline!($($t),*))
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{HNil, Handle};
+    use futures::executor::block_on;
+
+    type Result = std::result::Result<(), &'static str>;
+
+    #[derive(Default)]
+    struct Context {
+        fail: bool,
+        trace: Vec<&'static str>,
+    }
+
+    async fn a(cx: &mut Context) -> Result {
+        cx.trace.push("a");
+        if cx.fail {
+            return Err("a");
+        }
+        Ok(())
+    }
+
+    async fn b(cx: &mut Context) -> Result {
+        cx.trace.push("b");
+        Ok(())
+    }
+
+    async fn c(cx: &mut Context) -> Result {
+        cx.trace.push("c");
+        Ok(())
+    }
+
+    #[test]
+    fn runs_in_order() {
+        let h = hpipeline!(c, a, b);
+
+        let mut cx = Context::default();
+        assert_eq!(block_on(h.call(&mut cx)), Ok(()));
+        assert_eq!(cx.trace, ["c", "a", "b"]);
+
+        let mut cx = Context {
+            fail: true,
+            ..Default::default()
+        };
+        assert_eq!(block_on(h.call(&mut cx)), Err("a"));
+        assert_eq!(cx.trace, ["c", "a"]);
+    }
+
+    #[test]
+    fn empty() {
+        let h: HNil<Result> = hpipeline!();
+
+        let mut cx = Context::default();
+        assert_eq!(block_on(h.call(&mut cx)), Ok(()));
+        assert!(cx.trace.is_empty());
+    }
+}
@@ -0,0 +1,165 @@
+use alloc::{boxed::Box, sync::Arc, vec::Vec};
+
+use crate::{BoxFuture, ContextExt, Empty, FromNextAlreadyCalled, Next};
+
+#[cfg(feature = "send")]
+type EnterHook<Context> = Arc<dyn Fn(&Context) + Send + Sync>;
+
+#[cfg(not(feature = "send"))]
+type EnterHook<Context> = Arc<dyn Fn(&Context)>;
+
+#[cfg(feature = "send")]
+type ExitHook<Context, Output> = Arc<dyn Fn(&Context, &Output) + Send + Sync>;
+
+#[cfg(not(feature = "send"))]
+type ExitHook<Context, Output> = Arc<dyn Fn(&Context, &Output)>;
+
+/// The hooks of a pipeline, run before its first handler and after it
+/// completes.
+pub(crate) struct Hooks<Context, Output> {
+    pub(crate) enter: Vec<EnterHook<Context>>,
+    pub(crate) exit: Vec<ExitHook<Context, Output>>,
+}
+
+impl<Context, Output> Hooks<Context, Output> {
+    /// Runs the hooks around the handlers of the cursor.
+    pub(crate) fn run<'a>(
+        self: &Arc<Self>,
+        next: Next<Context, Output>,
+        cx: &'a mut Context,
+    ) -> BoxFuture<'a, Output>
+    where
+        Context: ContextExt<Output>,
+        Output: Empty + FromNextAlreadyCalled + 'static,
+    {
+        if self.enter.is_empty() && self.exit.is_empty() {
+            return next.run_nested(cx);
+        }
+
+        let hooks = self.clone();
+        Box::pin(async move {
+            for f in &hooks.enter {
+                unwind_safe(|| f(cx));
+            }
+            let output = next.run_nested(&mut *cx).await;
+            for f in &hooks.exit {
+                unwind_safe(|| f(cx, &output));
+            }
+            output
+        })
+    }
+}
+
+/// Calls the hook, catching its panic with `std`.
+fn unwind_safe(f: impl FnOnce()) {
+    #[cfg(feature = "std")]
+    let _ = std::panic::catch_unwind(std::panic::AssertUnwindSafe(f));
+    #[cfg(not(feature = "std"))]
+    f();
+}
+
+impl<Context, Output> Default for Hooks<Context, Output> {
+    fn default() -> Self {
+        Self {
+            enter: Vec::new(),
+            exit: Vec::new(),
+        }
+    }
+}
+
+impl<Context, Output> Clone for Hooks<Context, Output> {
+    fn clone(&self) -> Self {
+        Self {
+            enter: self.enter.clone(),
+            exit: self.exit.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ContextExt, Next, Pipeline};
+    use futures::executor::block_on;
+    use std::sync::{Arc, Mutex};
+
+    type Output = Option<usize>;
+
+    #[derive(Default)]
+    struct Context {
+        trace: Vec<&'static str>,
+        next: Next<Self, Output>,
+    }
+
+    impl ContextExt<Output> for Context {
+        fn next_mut(&mut self) -> &mut Next<Self, Output> {
+            &mut self.next
+        }
+
+        fn next_ref(&self) -> &Next<Self, Output> {
+            &self.next
+        }
+    }
+
+    async fn a(cx: &mut Context) -> Output {
+        cx.trace.push("a");
+        cx.next().await;
+        Some(cx.trace.len())
+    }
+
+    async fn b(cx: &mut Context) -> Output {
+        cx.trace.push("b");
+        None
+    }
+
+    #[test]
+    fn order_and_state() {
+        let log = Arc::new(Mutex::new(Vec::new()));
+
+        let mut pipeline = Pipeline::new();
+        pipeline.push(a).push(b);
+        for name in ["enter 1", "enter 2"] {
+            let log = log.clone();
+            pipeline.on_enter(move |cx: &Context| {
+                log.lock().unwrap().push(format!("{name}: {:?}", cx.trace));
+            });
+        }
+        for name in ["exit 1", "exit 2"] {
+            let log = log.clone();
+            pipeline.on_exit(move |cx: &Context, output: &Output| {
+                log.lock()
+                    .unwrap()
+                    .push(format!("{name}: {:?} {output:?}", cx.trace));
+            });
+        }
+
+        let mut cx = Context::default();
+        assert_eq!(block_on(pipeline.freeze().run(&mut cx)), Some(2));
+        assert_eq!(
+            *log.lock().unwrap(),
+            [
+                "enter 1: []",
+                "enter 2: []",
+                r#"exit 1: ["a", "b"] Some(2)"#,
+                r#"exit 2: ["a", "b"] Some(2)"#,
+            ]
+        );
+    }
+
+    #[test]
+    fn panicking_hooks() {
+        let log = Arc::new(Mutex::new(Vec::new()));
+
+        let mut pipeline = Pipeline::new();
+        pipeline.push(a).push(b);
+        pipeline.on_enter(|_: &Context| panic!("enter"));
+        pipeline.on_exit(|_: &Context, _: &Output| panic!("exit"));
+        let exit = log.clone();
+        pipeline.on_exit(move |_: &Context, output: &Output| {
+            exit.lock().unwrap().push(*output);
+        });
+
+        let mut cx = Context::default();
+        assert_eq!(block_on(pipeline.run(&mut cx)), Some(2));
+        assert_eq!(*log.lock().unwrap(), [Some(2)]);
+    }
+}
@@ -0,0 +1,171 @@
+use tracing::Instrument;
+
+use crate::{BoxFuture, Handle};
+
+/// Instruments the handler with a [`tracing::Span`] for each call.
+///
+/// The span is entered while the handler builds its future and whenever the
+/// future is polled, and the output variant (`ok` or `err`) is recorded as an
+/// event inside it.
+///
+/// Only handlers returning a `Result<T, E>` can be instrumented.
+#[derive(Debug, Clone)]
+pub struct Instrumented<H> {
+    h: H,
+    name: &'static str,
+}
+
+impl<H> Instrumented<H> {
+    /// Creates a new [`Instrumented`].
+    #[inline]
+    pub const fn new(h: H, name: &'static str) -> Self {
+        Self { h, name }
+    }
+}
+
+impl<'a, Context, H, T, E> Handle<'a, Context> for Instrumented<H>
+where
+    H: Handle<'a, Context, Output = Result<T, E>>,
+    Context: 'a,
+    T: 'a,
+    E: 'a,
+{
+    type Output = Result<T, E>;
+
+    fn call(&'a self, cx: &'a mut Context) -> BoxFuture<'a, Self::Output> {
+        let span = tracing::debug_span!("handle", name = self.name);
+        let fut = span.in_scope(|| self.h.call(cx));
+
+        Box::pin(
+            async move {
+                let output = fut.await;
+                match output {
+                    Ok(_) => tracing::debug!(output = "ok"),
+                    Err(_) => tracing::debug!(output = "err"),
+                }
+                output
+            }
+            .instrument(span),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ArcHandle, BoxFuture, Handle, HandleExt};
+    use anyhow::{anyhow, Result};
+    use futures::executor::block_on;
+    use std::{
+        io,
+        sync::{Arc, Mutex},
+    };
+
+    struct Context {
+        middleware: Vec<ArcHandle<Context, Result<()>>>,
+    }
+
+    impl Context {
+        async fn next(&mut self) -> Result<()> {
+            if let Some(m) = self.middleware.pop() {
+                m.call(self).await
+            } else {
+                Ok(())
+            }
+        }
+    }
+
+    async fn a(cx: &mut Context) -> Result<()> {
+        cx.next().await
+    }
+
+    async fn b(cx: &mut Context) -> Result<()> {
+        cx.next().await
+    }
+
+    async fn c(_: &mut Context) -> Result<()> {
+        Err(anyhow!("c"))
+    }
+
+    struct Eager;
+
+    impl<'a> Handle<'a, Context> for Eager {
+        type Output = Result<()>;
+
+        fn call(&'a self, _: &'a mut Context) -> BoxFuture<'a, Self::Output> {
+            tracing::debug!("called");
+            Box::pin(async { Ok(()) })
+        }
+    }
+
+    #[derive(Clone, Default)]
+    struct Writer(Arc<Mutex<Vec<u8>>>);
+
+    impl io::Write for Writer {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    /// Runs `f` with a subscriber logging the spans and events, and returns
+    /// its output with the logged lines.
+    fn capture<T>(f: impl FnOnce() -> T) -> (T, Vec<String>) {
+        let writer = Writer::default();
+        let w = writer.clone();
+        let subscriber = tracing_subscriber::fmt()
+            .with_max_level(tracing::Level::DEBUG)
+            .with_writer(move || w.clone())
+            .with_ansi(false)
+            .without_time()
+            .with_target(false)
+            .with_level(false)
+            .finish();
+
+        let output = tracing::subscriber::with_default(subscriber, f);
+        let logs = String::from_utf8(writer.0.lock().unwrap().clone()).unwrap();
+        (output, logs.lines().map(String::from).collect())
+    }
+
+    #[test]
+    fn spans_follow_pipeline() {
+        let mut cx = Context {
+            middleware: vec![
+                Arc::new(c.traced("c")),
+                Arc::new(b.traced("b")),
+                Arc::new(a.traced("a")),
+            ],
+        };
+
+        let (result, lines) = capture(|| block_on(cx.next()));
+        assert!(result.is_err());
+        assert_eq!(
+            lines,
+            [
+                r#"handle{name="a"}:handle{name="b"}:handle{name="c"}: output="err""#,
+                r#"handle{name="a"}:handle{name="b"}: output="err""#,
+                r#"handle{name="a"}: output="err""#,
+            ]
+        );
+    }
+
+    #[test]
+    fn span_entered_on_call() {
+        let mut cx = Context {
+            middleware: vec![Arc::new(Eager.traced("eager"))],
+        };
+
+        let (result, lines) = capture(|| block_on(cx.next()));
+        assert!(result.is_ok());
+        assert_eq!(
+            lines,
+            [
+                r#"handle{name="eager"}: called"#,
+                r#"handle{name="eager"}: output="ok""#,
+            ]
+        );
+    }
+}
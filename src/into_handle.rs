@@ -0,0 +1,257 @@
+use alloc::sync::Arc;
+use core::any::TypeId;
+
+use crate::{ArcHandle, BoxHandle, Handle, NamedHandle};
+
+/// Conversion into a shared [`Handle`] trait object, taken by the registration
+/// points of [`Pipeline`](crate::Pipeline).
+///
+/// It is implemented for every [`Handle`], which includes async functions and
+/// handler structs, for the [`BoxHandle`] and [`ArcHandle`] trait objects, and
+/// for `(name, handler)` tuples naming the handler like
+/// [`HandleExt::named`](crate::HandleExt::named).
+///
+/// The trait objects are not wrapped again: an [`ArcHandle`] is taken as is
+/// and a [`BoxHandle`] is moved into an [`Arc`], so the pipeline sees the type
+/// of the handler inside, e.g. for [`Pipeline::contains`]. Share a handler
+/// struct as an [`ArcHandle`], an `Arc<H>` is not a [`Handle`].
+///
+/// The `Kind` parameter tells the impls apart, see [`kind`]; it is inferred,
+/// so a registration point takes any `H: IntoHandle<Context, Output, K>`.
+///
+/// A closure can't return a future borrowing its argument, so an inline
+/// closure must return a [`BoxFuture`](crate::BoxFuture), see
+/// [`Pipeline::push_fn`](crate::Pipeline::push_fn).
+///
+/// [`Pipeline::contains`]: crate::Pipeline::contains
+pub trait IntoHandle<Context, Output, Kind = kind::Handler> {
+    /// Converts the value into an [`ArcHandle`].
+    fn into_handle(self) -> ArcHandle<Context, Output>;
+
+    /// Returns the type of the handler [`IntoHandle::into_handle`] makes of
+    /// the value, the handler inside for the trait objects.
+    fn handler_type_id(&self) -> TypeId;
+}
+
+/// The markers of the [`IntoHandle`] impls.
+///
+/// The trait objects do not implement [`Handle`], so exactly one impl applies
+/// to each value and the marker is always inferred.
+pub mod kind {
+    /// A [`Handle`](crate::Handle), shared as it is.
+    #[derive(Debug)]
+    pub enum Handler {}
+
+    /// An [`ArcHandle`](crate::ArcHandle), taken as is.
+    #[derive(Debug)]
+    pub enum Shared {}
+
+    /// A [`BoxHandle`](crate::BoxHandle), moved into an `Arc`.
+    #[derive(Debug)]
+    pub enum Boxed {}
+
+    /// A `(name, handler)` tuple, naming the handler.
+    #[derive(Debug)]
+    pub enum Named {}
+}
+
+impl<Context, Output, H> IntoHandle<Context, Output> for H
+where
+    H: for<'a> Handle<'a, Context, Output = Output>,
+    Context: 'static,
+    Output: 'static,
+{
+    #[inline]
+    fn into_handle(self) -> ArcHandle<Context, Output> {
+        Arc::new(self)
+    }
+
+    #[inline]
+    fn handler_type_id(&self) -> TypeId {
+        TypeId::of::<H>()
+    }
+}
+
+impl<Context, Output> IntoHandle<Context, Output, kind::Shared> for ArcHandle<Context, Output>
+where
+    Context: 'static,
+    Output: 'static,
+{
+    #[inline]
+    fn into_handle(self) -> ArcHandle<Context, Output> {
+        self
+    }
+
+    #[inline]
+    fn handler_type_id(&self) -> TypeId {
+        // Not `self.type_id()`, which would be the `Arc` itself.
+        (**self).as_any().type_id()
+    }
+}
+
+impl<Context, Output> IntoHandle<Context, Output, kind::Boxed> for BoxHandle<Context, Output>
+where
+    Context: 'static,
+    Output: 'static,
+{
+    #[inline]
+    fn into_handle(self) -> ArcHandle<Context, Output> {
+        Arc::from(self)
+    }
+
+    #[inline]
+    fn handler_type_id(&self) -> TypeId {
+        (**self).as_any().type_id()
+    }
+}
+
+impl<Context, Output, H> IntoHandle<Context, Output, kind::Named> for (&'static str, H)
+where
+    H: for<'a> Handle<'a, Context, Output = Output>,
+    Context: 'static,
+    Output: 'static,
+{
+    #[inline]
+    fn into_handle(self) -> ArcHandle<Context, Output> {
+        Arc::new(NamedHandle::new(self.0, self.1))
+    }
+
+    #[inline]
+    fn handler_type_id(&self) -> TypeId {
+        TypeId::of::<NamedHandle<H>>()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        ArcHandle, BoxFuture, BoxHandle, ContextExt, Handle, HandleExt, IntoHandle, Next, Pipeline,
+        WeakHandle,
+    };
+    use futures::executor::block_on;
+    use std::{any::TypeId, sync::Arc};
+
+    type Result = anyhow::Result<()>;
+
+    #[derive(Default)]
+    struct Context {
+        trace: Vec<&'static str>,
+        next: Next<Self, Result>,
+    }
+
+    impl ContextExt<Result> for Context {
+        fn next_mut(&mut self) -> &mut Next<Self, Result> {
+            &mut self.next
+        }
+
+        fn next_ref(&self) -> &Next<Self, Result> {
+            &self.next
+        }
+    }
+
+    async fn a(cx: &mut Context) -> Result {
+        cx.trace.push("a");
+        cx.next().await
+    }
+
+    async fn b(cx: &mut Context) -> Result {
+        cx.trace.push("b");
+        cx.next().await
+    }
+
+    async fn c(cx: &mut Context) -> Result {
+        cx.trace.push("c");
+        cx.next().await
+    }
+
+    struct D;
+
+    impl<'a> Handle<'a, Context> for D {
+        type Output = Result;
+
+        fn call(&'a self, cx: &'a mut Context) -> BoxFuture<'a, Self::Output> {
+            Box::pin(async move {
+                cx.trace.push("d");
+                cx.next().await
+            })
+        }
+    }
+
+    #[test]
+    fn every_shape() {
+        let boxed: BoxHandle<Context, Result> = Box::new(b);
+        let shared: ArcHandle<Context, Result> = Arc::new(c);
+
+        let mut pipeline = Pipeline::new();
+        pipeline
+            .push(a)
+            .push(boxed)
+            .push(shared)
+            .push(D)
+            .push(("e", D))
+            .push_fn(|cx| {
+                Box::pin(async move {
+                    cx.trace.push("f");
+                    cx.next().await
+                })
+            });
+        assert_eq!(pipeline.names()[4], "e");
+
+        let mut cx = Context::default();
+        assert!(block_on(pipeline.run(&mut cx)).is_ok());
+        assert_eq!(cx.trace, ["a", "b", "c", "d", "d", "f"]);
+    }
+
+    #[test]
+    fn keeps_name() {
+        let h = D.named("auth").into_handle();
+        assert_eq!(h.name(), "auth");
+
+        let h = ("auth", D).into_handle();
+        assert_eq!(h.name(), "auth");
+
+        let shared: ArcHandle<Context, Result> = Arc::new(a);
+        assert_eq!(shared.clone().into_handle().name(), shared.name());
+    }
+
+    #[test]
+    fn shared_struct() {
+        let d: ArcHandle<Context, Result> = Arc::new(D);
+
+        let mut pipeline = Pipeline::new();
+        pipeline.push(d.clone()).push(d);
+        assert!(pipeline.contains::<D>());
+
+        let mut cx = Context::default();
+        assert!(block_on(pipeline.run(&mut cx)).is_ok());
+        assert_eq!(cx.trace, ["d", "d"]);
+    }
+
+    #[test]
+    fn not_wrapped_again() {
+        let shared: ArcHandle<Context, Result> = Arc::new(D);
+        assert_eq!(shared.handler_type_id(), TypeId::of::<D>());
+        let h = shared.clone().into_handle();
+        assert!(Arc::ptr_eq(&h, &shared));
+
+        let boxed: BoxHandle<Context, Result> = Box::new(D);
+        assert_eq!(boxed.handler_type_id(), TypeId::of::<D>());
+        assert!(boxed.into_handle().is::<D>());
+
+        let mut pipeline = Pipeline::new();
+        pipeline.push(shared.clone());
+        assert!(pipeline.contains::<D>());
+        assert!(!pipeline.contains::<ArcHandle<Context, Result>>());
+        assert!(pipeline.push_unique(shared).is_err());
+        assert!(pipeline.push_unique(D).is_err());
+
+        // A `WeakHandle` pushed as an `ArcHandle` is still pruned.
+        let dropped: ArcHandle<Context, Result> = Arc::new(a);
+        let weak = WeakHandle::downgrade(&dropped).into_handle();
+        drop(dropped);
+        pipeline.push(weak);
+        assert_eq!(pipeline.len(), 2);
+        assert_eq!(pipeline.prune(), 1);
+        assert_eq!(pipeline.len(), 1);
+    }
+}
@@ -0,0 +1,209 @@
+use alloc::{boxed::Box, vec::Vec};
+use core::{
+    future::{poll_fn, Future},
+    task::Poll,
+};
+
+use crate::{BoxFuture, Handle, MaybeSend, MaybeSync};
+
+/// Runs two handlers concurrently on the disjoint parts of the context split
+/// by `split`, and returns both outputs.
+#[inline]
+pub const fn join<S, A, B, Context, CxA, CxB>(split: S, a: A, b: B) -> Join<S, A, B>
+where
+    S: Fn(&mut Context) -> (&mut CxA, &mut CxB),
+{
+    Join { split, a, b }
+}
+
+/// Runs two handlers concurrently on the disjoint parts of the context split
+/// by `split`, and returns both outputs or the first error.
+///
+/// The other handler is dropped as soon as one of them fails.
+#[inline]
+pub const fn try_join<S, A, B, Context, CxA, CxB>(split: S, a: A, b: B) -> TryJoin<S, A, B>
+where
+    S: Fn(&mut Context) -> (&mut CxA, &mut CxB),
+{
+    TryJoin { split, a, b }
+}
+
+/// Polls all the futures concurrently, and returns their outputs in order.
+pub(crate) fn join_all<'a, Output>(
+    mut futures: Vec<BoxFuture<'a, Output>>,
+) -> impl Future<Output = Vec<Output>> + MaybeSend + 'a
+where
+    Output: MaybeSend + 'a,
+{
+    let mut outputs: Vec<Option<Output>> = futures.iter().map(|_| None).collect();
+
+    poll_fn(move |task| {
+        let mut ready = true;
+        for (fut, output) in futures.iter_mut().zip(outputs.iter_mut()) {
+            if output.is_none() {
+                match fut.as_mut().poll(task) {
+                    Poll::Ready(o) => *output = Some(o),
+                    Poll::Pending => ready = false,
+                }
+            }
+        }
+        if ready {
+            Poll::Ready(outputs.iter_mut().filter_map(Option::take).collect())
+        } else {
+            Poll::Pending
+        }
+    })
+}
+
+/// The handler returned by [`join`].
+#[derive(Debug, Clone)]
+pub struct Join<S, A, B> {
+    split: S,
+    a: A,
+    b: B,
+}
+
+impl<'a, Context, S, A, B, CxA, CxB> Handle<'a, Context> for Join<S, A, B>
+where
+    S: Fn(&mut Context) -> (&mut CxA, &mut CxB) + MaybeSend + MaybeSync + 'static,
+    A: Handle<'a, CxA>,
+    B: Handle<'a, CxB>,
+    A::Output: MaybeSend + 'a,
+    B::Output: MaybeSend + 'a,
+    CxA: 'a,
+    CxB: 'a,
+{
+    type Output = (A::Output, B::Output);
+
+    fn call(&'a self, cx: &'a mut Context) -> BoxFuture<'a, Self::Output> {
+        let (a, b) = (self.split)(cx);
+        let (mut a, mut b) = (self.a.call(a), self.b.call(b));
+        let (mut oa, mut ob) = (None, None);
+
+        Box::pin(poll_fn(move |task| {
+            if oa.is_none() {
+                if let Poll::Ready(o) = a.as_mut().poll(task) {
+                    oa = Some(o);
+                }
+            }
+            if ob.is_none() {
+                if let Poll::Ready(o) = b.as_mut().poll(task) {
+                    ob = Some(o);
+                }
+            }
+            match (oa.take(), ob.take()) {
+                (Some(a), Some(b)) => Poll::Ready((a, b)),
+                (a, b) => {
+                    (oa, ob) = (a, b);
+                    Poll::Pending
+                }
+            }
+        }))
+    }
+}
+
+/// The handler returned by [`try_join`].
+#[derive(Debug, Clone)]
+pub struct TryJoin<S, A, B> {
+    split: S,
+    a: A,
+    b: B,
+}
+
+impl<'a, Context, S, A, B, CxA, CxB, T, U, E> Handle<'a, Context> for TryJoin<S, A, B>
+where
+    S: Fn(&mut Context) -> (&mut CxA, &mut CxB) + MaybeSend + MaybeSync + 'static,
+    A: Handle<'a, CxA, Output = Result<T, E>>,
+    B: Handle<'a, CxB, Output = Result<U, E>>,
+    T: MaybeSend + 'a,
+    U: MaybeSend + 'a,
+    E: MaybeSend + 'a,
+    CxA: 'a,
+    CxB: 'a,
+{
+    type Output = Result<(T, U), E>;
+
+    fn call(&'a self, cx: &'a mut Context) -> BoxFuture<'a, Self::Output> {
+        let (a, b) = (self.split)(cx);
+        let (mut a, mut b) = (self.a.call(a), self.b.call(b));
+        let (mut oa, mut ob) = (None, None);
+
+        Box::pin(poll_fn(move |task| {
+            if oa.is_none() {
+                if let Poll::Ready(o) = a.as_mut().poll(task) {
+                    oa = Some(o?);
+                }
+            }
+            if ob.is_none() {
+                if let Poll::Ready(o) = b.as_mut().poll(task) {
+                    ob = Some(o?);
+                }
+            }
+            match (oa.take(), ob.take()) {
+                (Some(a), Some(b)) => Poll::Ready(Ok((a, b))),
+                (a, b) => {
+                    (oa, ob) = (a, b);
+                    Poll::Pending
+                }
+            }
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{join, try_join, Handle};
+    use anyhow::{anyhow, Result};
+    use async_std::task::sleep;
+    use std::time::{Duration, Instant};
+
+    #[derive(Default)]
+    struct Span {
+        start: Option<Instant>,
+        end: Option<Instant>,
+        fail: bool,
+    }
+
+    #[derive(Default)]
+    struct Context {
+        auth: Span,
+        cache: Span,
+    }
+
+    fn split(cx: &mut Context) -> (&mut Span, &mut Span) {
+        (&mut cx.auth, &mut cx.cache)
+    }
+
+    async fn work(cx: &mut Span) -> Result<Duration> {
+        let start = Instant::now();
+        cx.start = Some(start);
+        sleep(Duration::from_millis(50)).await;
+        if cx.fail {
+            return Err(anyhow!("boom"));
+        }
+        cx.end = Some(Instant::now());
+        Ok(start.elapsed())
+    }
+
+    #[async_std::test]
+    async fn overlap() {
+        let mut cx = Context::default();
+        let (a, b) = join(split, work, work).call(&mut cx).await;
+        assert!(a.is_ok() && b.is_ok());
+
+        let (auth, cache) = (&cx.auth, &cx.cache);
+        assert!(auth.start.unwrap() < cache.end.unwrap());
+        assert!(cache.start.unwrap() < auth.end.unwrap());
+    }
+
+    #[async_std::test]
+    async fn first_error() {
+        let mut cx = Context::default();
+        assert!(try_join(split, work, work).call(&mut cx).await.is_ok());
+
+        let mut cx = Context::default();
+        cx.cache.fail = true;
+        assert!(try_join(split, work, work).call(&mut cx).await.is_err());
+        assert!(cx.cache.end.is_none());
+    }
+}
@@ -0,0 +1,248 @@
+use std::{
+    fmt,
+    sync::{Mutex, OnceLock, PoisonError},
+};
+
+use crate::{BoxFuture, Handle, MaybeSend, MaybeSync};
+
+/// Builds the handler on the first call and reuses it for all the following
+/// calls, see [`lazy`].
+///
+/// The builder runs at most once, even when the first calls race.
+pub struct Lazy<F, H> {
+    f: F,
+    h: OnceLock<H>,
+}
+
+/// Creates a [`Lazy`] handler built by `f` on its first call.
+#[inline]
+pub const fn lazy<F, H>(f: F) -> Lazy<F, H>
+where
+    F: Fn() -> H,
+{
+    Lazy {
+        f,
+        h: OnceLock::new(),
+    }
+}
+
+impl<F, H> Lazy<F, H> {
+    /// Returns the handler if it has been built.
+    #[inline]
+    pub fn get(&self) -> Option<&H> {
+        self.h.get()
+    }
+}
+
+impl<'a, Context, F, H> Handle<'a, Context> for Lazy<F, H>
+where
+    F: Fn() -> H + MaybeSend + MaybeSync + 'static,
+    H: Handle<'a, Context>,
+{
+    type Output = H::Output;
+
+    #[inline]
+    fn call(&'a self, cx: &'a mut Context) -> BoxFuture<'a, Self::Output> {
+        self.h.get_or_init(&self.f).call(cx)
+    }
+}
+
+impl<F, H> fmt::Debug for Lazy<F, H> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Lazy")
+            .field("handler", &core::any::type_name::<H>())
+            .field("built", &self.h.get().is_some())
+            .finish()
+    }
+}
+
+/// Builds the handler with a fallible builder on the first call, see
+/// [`try_lazy`].
+///
+/// While the builder fails, the calls return its error converted into the
+/// error of the handler. The builder runs again on the next call, unless the
+/// error is cached with [`TryLazy::cache_errors`].
+pub struct TryLazy<F, H, E> {
+    f: F,
+    h: OnceLock<H>,
+    /// Serializes the builds, and holds the cached error.
+    failed: Mutex<Option<E>>,
+    cache_errors: bool,
+}
+
+/// Creates a [`TryLazy`] handler built by `f` on its first successful call.
+#[inline]
+pub const fn try_lazy<F, H, E>(f: F) -> TryLazy<F, H, E>
+where
+    F: Fn() -> Result<H, E>,
+{
+    TryLazy {
+        f,
+        h: OnceLock::new(),
+        failed: Mutex::new(None),
+        cache_errors: false,
+    }
+}
+
+impl<F, H, E> TryLazy<F, H, E> {
+    /// Keeps the first error of the builder and returns it for all the
+    /// following calls, instead of running the builder again.
+    #[must_use]
+    pub fn cache_errors(mut self) -> Self {
+        self.cache_errors = true;
+        self
+    }
+
+    /// Returns the handler if it has been built.
+    #[inline]
+    pub fn get(&self) -> Option<&H> {
+        self.h.get()
+    }
+
+    fn get_or_try_init(&self) -> Result<&H, E>
+    where
+        F: Fn() -> Result<H, E>,
+        E: Clone,
+    {
+        if let Some(h) = self.h.get() {
+            return Ok(h);
+        }
+        let mut failed = self.failed.lock().unwrap_or_else(PoisonError::into_inner);
+        if let Some(h) = self.h.get() {
+            return Ok(h);
+        }
+        if let Some(e) = &*failed {
+            return Err(e.clone());
+        }
+        match (self.f)() {
+            Ok(h) => Ok(self.h.get_or_init(|| h)),
+            Err(e) => {
+                if self.cache_errors {
+                    *failed = Some(e.clone());
+                }
+                Err(e)
+            }
+        }
+    }
+}
+
+impl<'a, Context, F, H, E, T, HE> Handle<'a, Context> for TryLazy<F, H, E>
+where
+    F: Fn() -> Result<H, E> + MaybeSend + MaybeSync + 'static,
+    H: Handle<'a, Context, Output = Result<T, HE>>,
+    E: Clone + MaybeSend + 'static,
+    T: MaybeSend + 'a,
+    HE: From<E> + MaybeSend + 'a,
+{
+    type Output = Result<T, HE>;
+
+    fn call(&'a self, cx: &'a mut Context) -> BoxFuture<'a, Self::Output> {
+        match self.get_or_try_init() {
+            Ok(h) => h.call(cx),
+            Err(e) => Box::pin(async move { Err(e.into()) }),
+        }
+    }
+}
+
+impl<F, H, E> fmt::Debug for TryLazy<F, H, E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TryLazy")
+            .field("handler", &core::any::type_name::<H>())
+            .field("built", &self.h.get().is_some())
+            .field("cache_errors", &self.cache_errors)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{lazy, try_lazy, BoxFuture, Handle};
+    use futures::executor::block_on;
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    };
+
+    struct Matcher {
+        prefix: &'static str,
+    }
+
+    impl<'a> Handle<'a, &'static str> for Matcher {
+        type Output = Result<bool, String>;
+
+        fn call(&'a self, cx: &'a mut &'static str) -> BoxFuture<'a, Self::Output> {
+            Box::pin(async move { Ok(cx.starts_with(self.prefix)) })
+        }
+    }
+
+    #[test]
+    fn builds_once() {
+        let builds = Arc::new(AtomicUsize::new(0));
+        let h = {
+            let builds = builds.clone();
+            Arc::new(lazy(move || {
+                builds.fetch_add(1, Ordering::SeqCst);
+                Matcher { prefix: "/api" }
+            }))
+        };
+        assert!(h.get().is_none());
+
+        let threads: Vec<_> = (0..8)
+            .map(|_| {
+                let h = h.clone();
+                std::thread::spawn(move || {
+                    for _ in 0..100 {
+                        assert_eq!(block_on(h.call(&mut "/api/users")), Ok(true));
+                    }
+                })
+            })
+            .collect();
+        for thread in threads {
+            thread.join().unwrap();
+        }
+
+        assert_eq!(builds.load(Ordering::SeqCst), 1);
+        assert!(h.get().is_some());
+    }
+
+    fn flaky(builds: &Arc<AtomicUsize>) -> impl Fn() -> Result<Matcher, &'static str> {
+        let builds = builds.clone();
+        move || match builds.fetch_add(1, Ordering::SeqCst) {
+            0 | 1 => Err("pool unavailable"),
+            _ => Ok(Matcher { prefix: "/" }),
+        }
+    }
+
+    #[test]
+    fn retries_errors() {
+        let builds = Arc::new(AtomicUsize::new(0));
+        let h = try_lazy(flaky(&builds));
+
+        let outputs: Vec<_> = (0..4).map(|_| block_on(h.call(&mut "/"))).collect();
+        assert_eq!(
+            outputs,
+            [
+                Err("pool unavailable".to_string()),
+                Err("pool unavailable".to_string()),
+                Ok(true),
+                Ok(true),
+            ]
+        );
+        assert_eq!(builds.load(Ordering::SeqCst), 3);
+    }
+
+    #[test]
+    fn caches_errors() {
+        let builds = Arc::new(AtomicUsize::new(0));
+        let h = try_lazy(flaky(&builds)).cache_errors();
+
+        for _ in 0..4 {
+            assert_eq!(
+                block_on(h.call(&mut "/")),
+                Err("pool unavailable".to_string())
+            );
+        }
+        assert_eq!(builds.load(Ordering::SeqCst), 1);
+        assert!(h.get().is_none());
+    }
+}
@@ -0,0 +1,124 @@
+use alloc::boxed::Box;
+use core::{fmt, marker::PhantomData};
+
+use crate::{BoxFuture, Handle, MaybeSend, MaybeSync};
+
+/// Creates a fresh handler from a factory for each call.
+///
+/// The handler lives as long as the future of its call, so it can keep
+/// per-call state without an `Arc<Mutex<_>>`, and is dropped once the call
+/// completes.
+pub struct LazyHandle<F, H> {
+    f: F,
+    _h: PhantomData<fn() -> H>,
+}
+
+impl<F, H> LazyHandle<F, H> {
+    /// Creates a new [`LazyHandle`].
+    #[inline]
+    pub const fn new(f: F) -> Self
+    where
+        F: Fn() -> H,
+    {
+        Self { f, _h: PhantomData }
+    }
+}
+
+impl<'a, Context, F, H, O> Handle<'a, Context> for LazyHandle<F, H>
+where
+    F: Fn() -> H + MaybeSend + MaybeSync + 'static,
+    H: for<'b> Handle<'b, Context, Output = O>,
+    Context: MaybeSend + 'a,
+    O: 'a,
+{
+    type Output = O;
+
+    fn call(&'a self, cx: &'a mut Context) -> BoxFuture<'a, Self::Output> {
+        let h = (self.f)();
+        Box::pin(async move { h.call(&mut *cx).await })
+    }
+}
+
+impl<F: Clone, H> Clone for LazyHandle<F, H> {
+    fn clone(&self) -> Self {
+        Self {
+            f: self.f.clone(),
+            _h: PhantomData,
+        }
+    }
+}
+
+impl<F, H> fmt::Debug for LazyHandle<F, H> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("LazyHandle")
+            .field(&core::any::type_name::<H>())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{BoxFuture, Handle, LazyHandle};
+    use futures::executor::block_on;
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Mutex,
+    };
+
+    static LIVE: AtomicUsize = AtomicUsize::new(0);
+
+    #[derive(Default)]
+    struct Context {
+        keys: Vec<&'static str>,
+        touched: Vec<usize>,
+    }
+
+    struct RequestTracer {
+        touched: Mutex<usize>,
+    }
+
+    impl RequestTracer {
+        fn new() -> Self {
+            LIVE.fetch_add(1, Ordering::Relaxed);
+            Self {
+                touched: Mutex::new(0),
+            }
+        }
+    }
+
+    impl Drop for RequestTracer {
+        fn drop(&mut self) {
+            LIVE.fetch_sub(1, Ordering::Relaxed);
+        }
+    }
+
+    impl<'a> Handle<'a, Context> for RequestTracer {
+        type Output = ();
+
+        fn call(&'a self, cx: &'a mut Context) -> BoxFuture<'a, Self::Output> {
+            Box::pin(async move {
+                for _ in &cx.keys {
+                    *self.touched.lock().unwrap() += 1;
+                }
+                cx.touched.push(*self.touched.lock().unwrap());
+                assert_eq!(LIVE.load(Ordering::Relaxed), 1);
+            })
+        }
+    }
+
+    #[test]
+    fn fresh_per_call() {
+        let h = LazyHandle::new(RequestTracer::new);
+
+        let mut cx = Context {
+            keys: vec!["a", "b"],
+            ..Default::default()
+        };
+        block_on(h.call(&mut cx));
+        block_on(h.call(&mut cx));
+
+        // Each call started from a fresh tracer, which was dropped after.
+        assert_eq!(cx.touched, [2, 2]);
+        assert_eq!(LIVE.load(Ordering::Relaxed), 0);
+    }
+}
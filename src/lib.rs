@@ -2,33 +2,45 @@
 //!
 //! Maintain context in multiple handlers.
 //!
+//! The crate is `#![no_std]` with `alloc` when the default `std` feature is
+//! disabled. The timing, cancellation and registry helpers need `std`.
+//!
+//! Handlers and their futures must be [`Send`] and [`Sync`] with the default
+//! `send` feature. Disable it on single-threaded targets such as `wasm32`, where
+//! futures are usually `!Send`.
+//!
+//! The `send` feature is not additive: enabling it adds bounds, so a `!Send`
+//! handler which builds without it fails to build once it is enabled. Cargo
+//! unifies the features of a crate across the build, so any other crate of
+//! the build depending on `handle` with its default features turns `send`
+//! back on. A crate relying on `send` being disabled needs every other
+//! dependent on `handle` in the build to disable the default features too.
+//!
 //! Examples
 //!
 //! ```
-//! use handle::{Handle, BoxFuture};
-//! use futures::executor::block_on;
-//! use std::{future::Future, sync::Arc};
+//! use handle::{BoxFuture, ContextExt, Handle, Next, Pipeline};
 //!
 //! type Result = anyhow::Result<()>;
 //!
+//! #[derive(Default)]
 //! struct Context {
 //!     index: usize,
-//!     middleware: Vec<Box<dyn for<'a> Handle<'a, Context, Output = Result>>>,
+//!     next: Next<Self, Result>,
 //! }
 //!
-//! impl Context {
-//!     async fn next(&mut self) -> Result {
-//!         if let Some(m) = self.middleware.pop() {
-//!             m.call(self).await
-//!         } else {
-//!             Ok(())
-//!         }
+//! impl ContextExt<Result> for Context {
+//!     fn next_mut(&mut self) -> &mut Next<Self, Result> {
+//!         &mut self.next
+//!     }
+//!
+//!     fn next_ref(&self) -> &Next<Self, Result> {
+//!         &self.next
 //!     }
 //! }
 //!
 //! async fn a(cx: &mut Context) -> Result {
-//!     let size = cx.middleware.len();
-//!     let repeat = "-".repeat(2 * size);
+//!     let repeat = "-".repeat(2 * cx.next_ref().depth());
 //!
 //!     println!("exec Fn a --{}>> {:>2}", repeat, cx.index);
 //!
@@ -41,7 +53,6 @@
 //!     fut
 //! }
 //!
-//! #[derive(Clone)]
 //! struct A {
 //!     index: usize,
 //! }
@@ -51,8 +62,7 @@
 //!
 //!     fn call(&'a self, cx: &'a mut Context) -> BoxFuture<'a, Self::Output> {
 //!         Box::pin(async move {
-//!             let size = cx.middleware.len();
-//!             let repeat = "-".repeat(2 * size);
+//!             let repeat = "-".repeat(2 * cx.next_ref().depth());
 //!
 //!             println!("exec St A --{}>> {:>2}", repeat, cx.index);
 //!
@@ -69,32 +79,354 @@
 //!
 //! #[async_std::main]
 //! async fn main() -> Result {
-//!     let mut cx = Context {
-//!         index: 0,
-//!         middleware: vec![Box::new(a), Box::new(A { index: 2 })],
-//!     };
+//!     // The handlers run in the order they are pushed: `A` first, then `a`.
+//!     let mut pipeline = Pipeline::new();
+//!     pipeline.push(A { index: 2 }).push(a);
+//!
+//!     // Each request gets its own context.
+//!     for _ in 0..2 {
+//!         let mut cx = Context::default();
+//!
+//!         pipeline.run(&mut cx).await?;
+//!         assert_eq!(cx.index, 2);
+//!     }
+//!
+//!     Ok(())
+//! }
+//! ```
+//!
+//! Handlers of different types are stored as trait objects, with the
+//! [`DynHandle`], [`BoxHandle`] and [`ArcHandle`] aliases:
+//!
+//! ```
+//! use handle::{ArcHandle, BoxHandle, Handle};
+//!
+//! type Result = anyhow::Result<()>;
+//!
+//! struct Context {
+//!     hits: usize,
+//! }
 //!
-//!     let result = cx.next().await;
-//!     assert!(result.is_ok());
-//!     assert_eq!(result.unwrap(), ());
+//! async fn count(cx: &mut Context) -> Result {
+//!     cx.hits += 1;
+//!     Ok(())
+//! }
 //!
+//! async fn reset(cx: &mut Context) -> Result {
+//!     cx.hits = 0;
 //!     Ok(())
 //! }
+//!
+//! # futures::executor::block_on(async {
+//! let boxed: Vec<BoxHandle<Context, Result>> = vec![Box::new(count), Box::new(count)];
+//! let shared: Vec<ArcHandle<Context, Result>> = vec![std::sync::Arc::new(reset)];
+//!
+//! let mut cx = Context { hits: 0 };
+//! for h in &boxed {
+//!     h.call(&mut cx).await?;
+//! }
+//! assert_eq!(cx.hits, 2);
+//!
+//! for h in shared.iter().cloned() {
+//!     h.call(&mut cx).await?;
+//! }
+//! assert_eq!(cx.hits, 0);
+//! # Result::Ok(())
+//! # }).unwrap();
 //! ```
 
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
 #![forbid(unsafe_code, rust_2018_idioms)]
 #![deny(missing_debug_implementations, nonstandard_style)]
 #![warn(missing_docs, rustdoc::missing_doc_code_examples, unreachable_pub)]
+// The original tests bind the unit outputs of their handlers.
+#![cfg_attr(test, allow(clippy::let_unit_value))]
+
+extern crate alloc;
+
+use alloc::boxed::Box;
+
+mod all_errors;
+pub use all_errors::AllErrors;
+
+mod branch;
+pub use branch::{branch, Branch};
+
+mod catch;
+pub use catch::Catch;
+
+mod chain;
+pub use chain::Chain;
+
+mod clone;
+pub use clone::{BoxCloneHandle, CloneHandle};
+
+mod coerce;
+pub use coerce::{coerce, Coerce, IntoOutput};
+
+mod control;
+pub use control::{PipelineControl, Stoppable};
+
+mod depth;
+pub use depth::{DepthExceeded, FromDepthExceeded};
+
+mod empty;
+pub use empty::Empty;
+
+mod endpoint;
+pub use endpoint::Endpoint;
+
+mod erased;
+pub use erased::ErasedHandle;
+
+mod error_handle;
+pub use error_handle::{match_error, ErrorHandle, ErrorPredicate, MatchError};
+
+mod ext;
+pub use ext::HandleExt;
+
+mod fanout;
+pub use fanout::{fanout, fanout_merge, select_ok};
+
+mod fallback;
+pub use fallback::{Fallback, FallbackChain};
+
+mod group;
+pub use group::{HandleGroup, HandleGroupBuilder};
+
+pub mod guard;
+
+mod hlist;
+pub use hlist::{HCons, HNil};
+
+mod hooks;
+
+mod join;
+pub use join::{join, try_join, Join, TryJoin};
+
+mod into_handle;
+pub use into_handle::{kind, IntoHandle};
+
+mod lazy_handle;
+pub use lazy_handle::LazyHandle;
+
+mod map_context;
+pub use map_context::MapContext;
+
+mod named;
+pub use named::NamedHandle;
+
+mod next;
+pub use next::{ContextExt, FromNextAlreadyCalled, Next, NextAlreadyCalled};
+
+mod once;
+pub use once::HandleOnce;
+
+mod order;
+pub use order::{validate_pipeline, HandlerMeta, Meta, OrderViolation, Ordered};
+
+mod pipeline;
+pub use pipeline::{Order, Pipeline};
+
+pub mod prelude;
+
+mod race;
+pub use race::{race, Race, Winner};
+
+mod read;
+pub use read::{read_only, ReadHandle, ReadOnly, ReadPipeline};
+
+mod snapshot;
+pub use snapshot::Snapshot;
+
+mod stack;
+pub use stack::Stack;
+
+mod take;
+pub use take::Take;
+
+mod tuple;
+pub use tuple::TryTuple;
+
+mod until_break;
+pub use until_break::{IntoControlFlow, UntilBreak};
+
+mod walker;
+pub use walker::PipelineWalker;
+
+mod weak;
+pub use weak::WeakHandle;
+
+pub mod wrap;
+
+#[cfg(feature = "async-trait")]
+mod async_handle;
+#[cfg(feature = "async-trait")]
+pub use async_handle::{AsyncHandle, FromAsyncTrait};
+
+#[cfg(feature = "log")]
+mod log_handle;
+#[cfg(feature = "log")]
+pub use log_handle::LogHandle;
+
+#[cfg(feature = "macros")]
+pub use handle_macros::{handler, Handle, HandlerMeta};
+
+#[cfg(feature = "dashmap")]
+mod memo;
+#[cfg(feature = "dashmap")]
+pub use memo::Memo;
+
+#[cfg(feature = "std")]
+mod budget;
+#[cfg(feature = "std")]
+pub use budget::{Budget, BudgetExhausted, WithBudget};
+
+#[cfg(feature = "std")]
+mod cancel;
+#[cfg(feature = "std")]
+pub use cancel::{CancelToken, Cancelled, FromCancelled};
+
+#[cfg(feature = "tokio-util")]
+mod cancellable;
+#[cfg(feature = "tokio-util")]
+pub use cancellable::Cancellable;
+
+#[cfg(feature = "std")]
+mod dispatch;
+#[cfg(feature = "std")]
+pub use dispatch::Dispatch;
+
+#[cfg(feature = "std")]
+mod fn_mut;
+#[cfg(feature = "std")]
+pub use fn_mut::FnMutHandle;
+
+#[cfg(feature = "std")]
+pub use once::OnceWrapper;
+
+#[cfg(feature = "std")]
+mod lazy;
+#[cfg(feature = "std")]
+pub use lazy::{lazy, try_lazy, Lazy, TryLazy};
+
+#[cfg(feature = "std")]
+mod panic_guard;
+#[cfg(feature = "std")]
+pub use panic_guard::PanicGuard;
+
+#[cfg(feature = "std")]
+mod registry;
+#[cfg(feature = "std")]
+pub use registry::HandlerRegistry;
+
+#[cfg(feature = "std")]
+mod timed;
+#[cfg(feature = "std")]
+pub use timed::{Timed, TimedHandle, TimedPipeline, Timings};
+
+#[cfg(feature = "streams")]
+mod stream;
+#[cfg(feature = "streams")]
+pub use stream::{BoxStream, Collect, HandleStream, StreamPipeline};
+
+#[cfg(feature = "test-util")]
+pub mod test;
+
+#[cfg(feature = "tokio")]
+mod shared;
+#[cfg(feature = "tokio")]
+pub use shared::{ReadOnlyState, SharedState};
+
+#[cfg(feature = "tokio")]
+mod throttle;
+#[cfg(feature = "tokio")]
+pub use throttle::Throttle;
+
+#[cfg(feature = "tracing")]
+mod instrument;
+#[cfg(feature = "tracing")]
+pub use instrument::Instrumented;
+
+#[doc(hidden)]
+pub mod __private {
+    pub use alloc::boxed::Box;
+}
+
+/// An owned dynamically typed [`Future`] for use in cases where you can't
+/// statically type your result or need to add some indirection.
+#[cfg(feature = "send")]
+pub type BoxFuture<'a, Output> =
+    core::pin::Pin<Box<dyn 'a + Send + core::future::Future<Output = Output>>>;
 
 /// An owned dynamically typed [`Future`] for use in cases where you can't
 /// statically type your result or need to add some indirection.
+///
+/// Without the `send` feature the future is not required to be [`Send`].
+#[cfg(not(feature = "send"))]
 pub type BoxFuture<'a, Output> =
-    std::pin::Pin<Box<dyn 'a + Send + std::future::Future<Output = Output>>>;
+    core::pin::Pin<Box<dyn 'a + core::future::Future<Output = Output>>>;
+
+/// [`Send`] with the `send` feature, a bound met by every type without it.
+#[cfg(feature = "send")]
+pub trait MaybeSend: Send {}
+
+#[cfg(feature = "send")]
+impl<T> MaybeSend for T where T: Send + ?Sized {}
+
+/// [`Send`] with the `send` feature, a bound met by every type without it.
+#[cfg(not(feature = "send"))]
+pub trait MaybeSend {}
+
+#[cfg(not(feature = "send"))]
+impl<T> MaybeSend for T where T: ?Sized {}
+
+/// [`Sync`] with the `send` feature, a bound met by every type without it.
+#[cfg(feature = "send")]
+pub trait MaybeSync: Sync {}
+
+#[cfg(feature = "send")]
+impl<T> MaybeSync for T where T: Sync + ?Sized {}
+
+/// [`Sync`] with the `send` feature, a bound met by every type without it.
+#[cfg(not(feature = "send"))]
+pub trait MaybeSync {}
+
+#[cfg(not(feature = "send"))]
+impl<T> MaybeSync for T where T: ?Sized {}
+
+/// A [`Handle`] trait object, for any lifetime of the context.
+///
+/// It is [`Send`] and [`Sync`] with the `send` feature, which the [`Handle`]
+/// supertraits require.
+pub type DynHandle<Context, Output> = dyn for<'a> Handle<'a, Context, Output = Output>;
+
+/// A boxed [`Handle`] trait object.
+pub type BoxHandle<Context, Output> = Box<DynHandle<Context, Output>>;
+
+/// A shared [`Handle`] trait object.
+pub type ArcHandle<Context, Output> = alloc::sync::Arc<DynHandle<Context, Output>>;
+
+/// Upcasts a value to [`Any`](core::any::Any), which allows downcasting trait objects.
+pub trait AsAny {
+    /// Returns the value as [`Any`](core::any::Any).
+    fn as_any(&self) -> &dyn core::any::Any;
+}
+
+impl<T> AsAny for T
+where
+    T: core::any::Any,
+{
+    #[inline]
+    fn as_any(&self) -> &dyn core::any::Any {
+        self
+    }
+}
 
 /// A handle trait for asynchronous context pipeline.
 pub trait Handle<'a, Context>
 where
-    Self: Send + Sync + 'static,
+    Self: AsAny + MaybeSend + MaybeSync + 'static,
 {
     /// The type of value produced on completion.
     type Output;
@@ -102,16 +434,57 @@ where
     /// Invokes the handler within the given `Context` and then returns `Output`.
     #[must_use]
     fn call(&'a self, cx: &'a mut Context) -> BoxFuture<'a, Self::Output>;
+
+    /// Returns the name of the handler, its type name by default.
+    fn name(&self) -> &str {
+        core::any::type_name::<Self>()
+    }
+}
+
+impl<Context, Output> dyn for<'a> Handle<'a, Context, Output = Output>
+where
+    Context: 'static,
+    Output: 'static,
+{
+    /// Returns `true` if the handler is of type `T`.
+    #[inline]
+    pub fn is<T>(&self) -> bool
+    where
+        T: core::any::Any,
+    {
+        self.as_any().is::<T>()
+    }
+
+    /// Returns a reference to the handler if it is of type `T`.
+    #[inline]
+    pub fn downcast_ref<T>(&self) -> Option<&T>
+    where
+        T: core::any::Any,
+    {
+        self.as_any().downcast_ref::<T>()
+    }
+}
+
+impl<Context, Output> core::fmt::Debug for dyn for<'a> Handle<'a, Context, Output = Output>
+where
+    Context: 'static,
+    Output: 'static,
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_tuple("Handle").field(&self.name()).finish()
+    }
 }
 
 impl<'a, Context, Output, F, Fut> Handle<'a, Context> for F
 where
-    F: Send + Sync + 'static + Fn(&'a mut Context) -> Fut,
-    Fut: std::future::Future<Output = Output> + Send + 'a,
+    F: MaybeSend + MaybeSync + 'static + Fn(&'a mut Context) -> Fut,
+    Fut: core::future::Future<Output = Output> + MaybeSend + 'a,
     Context: 'a,
 {
     type Output = Output;
 
+    // Kept from the first release, newer compilers warn it has no effect here.
+    #[allow(unused_attributes)]
     #[must_use]
     fn call(&'a self, cx: &'a mut Context) -> BoxFuture<'a, Self::Output> {
         Box::pin((self)(cx))
@@ -124,6 +497,7 @@ mod tests {
     use crate::{BoxFuture, Handle};
     use anyhow::Error;
     use futures::executor::block_on;
+    use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
     use std::{future::Future, sync::Arc};
 
     type Result = anyhow::Result<()>;
@@ -374,6 +748,57 @@ mod tests {
         }
     }
 
+    #[derive(Default)]
+    struct RateLimit {
+        disabled: AtomicBool,
+        hits: AtomicUsize,
+    }
+
+    impl<'a> Handle<'a, Context> for RateLimit {
+        type Output = Result;
+
+        fn call(&'a self, cx: &'a mut Context) -> BoxFuture<'a, Self::Output> {
+            Box::pin(async move {
+                if !self.disabled.load(Ordering::Relaxed) {
+                    self.hits.fetch_add(1, Ordering::Relaxed);
+                }
+                cx.next().await
+            })
+        }
+    }
+
+    #[test]
+    fn downcast() {
+        let v: Vec<Arc<Middleware>> = vec![
+            Arc::new(A { index: 1 }),
+            Arc::new(RateLimit::default()),
+            Arc::new(a),
+        ];
+
+        assert!(v[0].is::<A>());
+        assert!(v[1].is::<RateLimit>());
+        assert!(v[2].downcast_ref::<RateLimit>().is_none());
+
+        let run = || {
+            let mut cx = Context {
+                index: 0,
+                middleware: vec![v[1].clone()],
+            };
+            assert!(block_on(cx.next()).is_ok());
+        };
+        let rate_limit = v
+            .iter()
+            .find_map(|h| h.downcast_ref::<RateLimit>())
+            .unwrap();
+
+        run();
+        assert_eq!(rate_limit.hits.load(Ordering::Relaxed), 1);
+
+        rate_limit.disabled.store(true, Ordering::Relaxed);
+        run();
+        assert_eq!(rate_limit.hits.load(Ordering::Relaxed), 1);
+    }
+
     #[test]
     fn futures_rt() {
         assert!(block_on(async move {
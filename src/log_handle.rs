@@ -0,0 +1,120 @@
+use std::time::Instant;
+
+use log::Level;
+
+use crate::{BoxFuture, Handle, MaybeSend};
+
+/// Logs each call of the handler with the [`log`] crate.
+///
+/// A record is emitted when the handler is entered and another one with the
+/// elapsed time when it exits, both at [`Level::Debug`] unless changed with
+/// [`LogHandle::log_level`]. Unlike [`Instrumented`](crate::Instrumented) no
+/// span is propagated into the handler, so it works with any `log` backend.
+#[derive(Debug, Clone)]
+pub struct LogHandle<H> {
+    h: H,
+    name: &'static str,
+    level: Level,
+}
+
+impl<H> LogHandle<H> {
+    /// Creates a new [`LogHandle`] logging under `name`.
+    #[inline]
+    pub const fn new(h: H, name: &'static str) -> Self {
+        Self {
+            h,
+            name,
+            level: Level::Debug,
+        }
+    }
+
+    /// Sets the level of the records.
+    #[inline]
+    pub const fn log_level(mut self, level: Level) -> Self {
+        self.level = level;
+        self
+    }
+}
+
+impl<'a, Context, H> Handle<'a, Context> for LogHandle<H>
+where
+    H: Handle<'a, Context>,
+    Context: MaybeSend + 'a,
+{
+    type Output = H::Output;
+
+    fn call(&'a self, cx: &'a mut Context) -> BoxFuture<'a, Self::Output> {
+        Box::pin(async move {
+            log::log!(self.level, "[{}] entering", self.name);
+            let start = Instant::now();
+            let output = self.h.call(cx).await;
+            log::log!(
+                self.level,
+                "[{}] exiting ({:?})",
+                self.name,
+                start.elapsed()
+            );
+            output
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Handle, HandleExt, LogHandle};
+    use futures::executor::block_on;
+    use log::{Level, LevelFilter, Log, Metadata, Record};
+    use std::sync::Mutex;
+
+    struct Logger(Mutex<Vec<(Level, String)>>);
+
+    impl Log for Logger {
+        fn enabled(&self, _: &Metadata<'_>) -> bool {
+            true
+        }
+
+        fn log(&self, record: &Record<'_>) {
+            self.0
+                .lock()
+                .unwrap()
+                .push((record.level(), record.args().to_string()));
+        }
+
+        fn flush(&self) {}
+    }
+
+    static LOGGER: Logger = Logger(Mutex::new(Vec::new()));
+
+    #[derive(Default)]
+    struct Context {
+        index: usize,
+    }
+
+    async fn add(cx: &mut Context) -> usize {
+        cx.index += 1;
+        cx.index
+    }
+
+    #[test]
+    fn logs_each_call() {
+        log::set_logger(&LOGGER).unwrap();
+        log::set_max_level(LevelFilter::Trace);
+
+        let named = LogHandle::new(add, "add").log_level(Level::Info);
+        let logged = add.logged();
+
+        let mut cx = Context::default();
+        assert_eq!(block_on(named.call(&mut cx)), 1);
+        assert_eq!(block_on(logged.call(&mut cx)), 2);
+
+        let records = LOGGER.0.lock().unwrap();
+        let name = std::any::type_name_of_val(&add);
+        assert_eq!(records.len(), 4);
+        assert_eq!(records[0], (Level::Info, "[add] entering".to_string()));
+        assert_eq!(records[1].0, Level::Info);
+        assert!(records[1].1.starts_with("[add] exiting ("));
+        assert_eq!(records[2], (Level::Debug, format!("[{name}] entering")));
+        assert_eq!(records[3].0, Level::Debug);
+        assert!(records[3].1.starts_with(&format!("[{name}] exiting (")));
+    }
+}
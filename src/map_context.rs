@@ -0,0 +1,137 @@
+use crate::{BoxFuture, Handle, MaybeSend, MaybeSync};
+
+/// Runs a handler of an inner context on a part of an outer context.
+///
+/// The projector borrows the inner context out of the outer one for the call,
+/// so handlers written for a sub-context join the pipelines of a larger one.
+/// The inner reference lives as long as the borrow of the outer context, so
+/// the mutations of the handler land in the outer context.
+///
+/// ```
+/// use futures::executor::block_on;
+/// use handle::{Handle, MapContext};
+///
+/// #[derive(Default)]
+/// struct Auth {
+///     user: Option<&'static str>,
+/// }
+///
+/// #[derive(Default)]
+/// struct Request {
+///     auth: Auth,
+/// }
+///
+/// async fn login(cx: &mut Auth) {
+///     cx.user = Some("alice");
+/// }
+///
+/// let mut cx = Request::default();
+/// block_on(MapContext::new(login, |cx: &mut Request| &mut cx.auth).call(&mut cx));
+/// assert_eq!(cx.auth.user, Some("alice"));
+/// ```
+///
+/// The projected reference can't outlive the call, so the outer context is
+/// borrowed until the returned future is dropped:
+///
+/// ```compile_fail,E0506
+/// # use futures::executor::block_on;
+/// # use handle::{Handle, MapContext};
+/// # #[derive(Default)]
+/// # struct Auth {
+/// #     user: Option<&'static str>,
+/// # }
+/// # #[derive(Default)]
+/// # struct Request {
+/// #     auth: Auth,
+/// # }
+/// # async fn login(cx: &mut Auth) {
+/// #     cx.user = Some("alice");
+/// # }
+/// let h = MapContext::new(login, |cx: &mut Request| &mut cx.auth);
+/// let mut cx = Request::default();
+/// let fut = h.call(&mut cx);
+/// cx.auth.user = None;
+/// block_on(fut);
+/// ```
+#[derive(Debug, Clone)]
+pub struct MapContext<F, H> {
+    h: H,
+    f: F,
+}
+
+impl<F, H> MapContext<F, H> {
+    /// Creates a new [`MapContext`].
+    #[inline]
+    pub const fn new<Outer, Inner>(h: H, f: F) -> Self
+    where
+        F: for<'cx> Fn(&'cx mut Outer) -> &'cx mut Inner,
+    {
+        Self { h, f }
+    }
+}
+
+impl<'a, Outer, Inner, F, H> Handle<'a, Outer> for MapContext<F, H>
+where
+    F: for<'cx> Fn(&'cx mut Outer) -> &'cx mut Inner + MaybeSend + MaybeSync + 'static,
+    H: Handle<'a, Inner>,
+    Inner: 'a,
+{
+    type Output = H::Output;
+
+    #[inline]
+    fn call(&'a self, cx: &'a mut Outer) -> BoxFuture<'a, Self::Output> {
+        self.h.call((self.f)(cx))
+    }
+
+    #[inline]
+    fn name(&self) -> &str {
+        self.h.name()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ArcHandle, Handle, MapContext};
+    use futures::executor::block_on;
+    use std::sync::Arc;
+
+    #[derive(Default)]
+    struct Session {
+        visits: usize,
+    }
+
+    #[derive(Default)]
+    struct Request {
+        path: &'static str,
+        session: Session,
+    }
+
+    async fn visit(cx: &mut Session) -> usize {
+        cx.visits += 1;
+        cx.visits
+    }
+
+    async fn path(cx: &mut Request) -> usize {
+        cx.path.len()
+    }
+
+    #[test]
+    fn map_context() {
+        let handlers: Vec<ArcHandle<Request, usize>> = vec![
+            Arc::new(MapContext::new(visit, |cx: &mut Request| &mut cx.session)),
+            Arc::new(path),
+        ];
+
+        let mut cx = Request {
+            path: "/users",
+            ..Default::default()
+        };
+        assert_eq!(block_on(handlers[0].call(&mut cx)), 1);
+        assert_eq!(block_on(handlers[0].call(&mut cx)), 2);
+        assert_eq!(block_on(handlers[1].call(&mut cx)), 6);
+        assert_eq!(cx.session.visits, 2);
+
+        let mut session = Session::default();
+        assert_eq!(block_on(Handle::call(&visit, &mut session)), 1);
+    }
+}
@@ -0,0 +1,262 @@
+use std::{fmt, hash::Hash, sync::Arc};
+
+use dashmap::DashMap;
+
+use crate::{BoxFuture, Handle, MaybeSend, MaybeSync};
+
+/// Caches the output of the handler by a key extracted from the context.
+///
+/// On a hit the cached output is cloned and the handler is not called. Every
+/// output is cached unless [`Memo::ok_only`] is set, and the cache is
+/// unbounded unless [`Memo::max_len`] is set; use [`Memo::invalidate`] to drop
+/// an output. Clones share the same cache.
+pub struct Memo<H, F, K, V> {
+    h: H,
+    key: F,
+    filter: Option<fn(&V) -> bool>,
+    max_len: usize,
+    cache: Arc<DashMap<K, V>>,
+}
+
+impl<H, F, K, V> Memo<H, F, K, V>
+where
+    K: Hash + Eq,
+{
+    /// Creates a new [`Memo`] with an empty cache.
+    pub fn new<Context>(h: H, key: F) -> Self
+    where
+        F: Fn(&Context) -> K,
+    {
+        Self {
+            h,
+            key,
+            filter: None,
+            max_len: usize::MAX,
+            cache: Arc::new(DashMap::new()),
+        }
+    }
+
+    /// Bounds the cache to `max_len` outputs: caching another key once it is
+    /// full evicts the first key in the iteration order of the [`DashMap`],
+    /// which follows its shards and not the insertion order. Concurrent calls
+    /// may exceed it briefly.
+    #[must_use]
+    pub fn max_len(mut self, max_len: usize) -> Self {
+        self.max_len = max_len;
+        self
+    }
+
+    /// Removes the cached output of the `key`, returning it.
+    pub fn invalidate(&self, key: &K) -> Option<V> {
+        self.cache.remove(key).map(|(_, v)| v)
+    }
+
+    /// Removes all the cached outputs.
+    pub fn clear(&self) {
+        self.cache.clear();
+    }
+
+    /// Returns the number of cached outputs.
+    pub fn len(&self) -> usize {
+        self.cache.len()
+    }
+
+    /// Returns `true` if nothing is cached.
+    pub fn is_empty(&self) -> bool {
+        self.cache.is_empty()
+    }
+
+    fn insert(&self, key: K, v: V)
+    where
+        K: Clone,
+    {
+        if self.max_len == 0 {
+            return;
+        }
+        if self.cache.len() >= self.max_len && !self.cache.contains_key(&key) {
+            // The guard of the iterator is released before removing.
+            let evicted = self.cache.iter().next().map(|e| e.key().clone());
+            if let Some(evicted) = evicted {
+                self.cache.remove(&evicted);
+            }
+        }
+        self.cache.insert(key, v);
+    }
+}
+
+impl<H, F, K, T, E> Memo<H, F, K, Result<T, E>> {
+    /// Caches the `Ok` outputs only, so a failed key is retried on the next
+    /// call.
+    #[must_use]
+    pub fn ok_only(mut self) -> Self {
+        self.filter = Some(Result::is_ok);
+        self
+    }
+}
+
+impl<'a, Context, H, F, K, V> Handle<'a, Context> for Memo<H, F, K, V>
+where
+    H: for<'b> Handle<'b, Context, Output = V>,
+    F: Fn(&Context) -> K + MaybeSend + MaybeSync + 'static,
+    K: Hash + Eq + Clone + MaybeSend + MaybeSync + 'static,
+    V: Clone + MaybeSend + MaybeSync + 'static,
+    Context: MaybeSend + 'a,
+{
+    type Output = V;
+
+    fn call(&'a self, cx: &'a mut Context) -> BoxFuture<'a, Self::Output> {
+        let key = (self.key)(cx);
+        // The guard of the map is released before awaiting the handler.
+        if let Some(v) = self.cache.get(&key).map(|v| v.clone()) {
+            return Box::pin(async move { v });
+        }
+
+        Box::pin(async move {
+            let v = self.h.call(&mut *cx).await;
+            if self.filter.is_none_or(|f| f(&v)) {
+                self.insert(key, v.clone());
+            }
+            v
+        })
+    }
+}
+
+impl<H, F, K, V> Clone for Memo<H, F, K, V>
+where
+    H: Clone,
+    F: Clone,
+{
+    fn clone(&self) -> Self {
+        Self {
+            h: self.h.clone(),
+            key: self.key.clone(),
+            filter: self.filter,
+            max_len: self.max_len,
+            cache: self.cache.clone(),
+        }
+    }
+}
+
+impl<H, F, K, V> fmt::Debug for Memo<H, F, K, V>
+where
+    H: fmt::Debug,
+    K: Hash + Eq,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Memo")
+            .field("h", &self.h)
+            .field("ok_only", &self.filter.is_some())
+            .field("max_len", &self.max_len)
+            .field("len", &self.cache.len())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Handle, Memo};
+    use futures::executor::block_on;
+
+    #[derive(Default)]
+    struct Context {
+        token: &'static str,
+        lookups: usize,
+    }
+
+    async fn validate(cx: &mut Context) -> Result<usize, String> {
+        cx.lookups += 1;
+        match cx.token {
+            "valid" => Ok(1),
+            _ => Err(format!("invalid token: {}", cx.token)),
+        }
+    }
+
+    fn token(cx: &Context) -> &'static str {
+        cx.token
+    }
+
+    #[test]
+    fn memo() {
+        let h = Memo::new(validate, token).ok_only();
+
+        let mut cx = Context {
+            token: "valid",
+            ..Default::default()
+        };
+        for _ in 0..3 {
+            assert_eq!(block_on(h.call(&mut cx)), Ok(1));
+        }
+        assert_eq!(cx.lookups, 1);
+
+        // The errors are not cached.
+        cx.token = "forged";
+        assert!(block_on(h.clone().call(&mut cx)).is_err());
+        assert!(block_on(h.call(&mut cx)).is_err());
+        assert_eq!(cx.lookups, 3);
+        assert_eq!(h.len(), 1);
+
+        assert_eq!(h.invalidate(&"valid"), Some(Ok(1)));
+        cx.token = "valid";
+        assert_eq!(block_on(h.call(&mut cx)), Ok(1));
+        assert_eq!(cx.lookups, 4);
+
+        h.clear();
+        assert!(h.is_empty());
+    }
+
+    #[test]
+    fn cache_errors() {
+        let h = Memo::new(validate, token);
+
+        let mut cx = Context {
+            token: "forged",
+            ..Default::default()
+        };
+        assert!(block_on(h.call(&mut cx)).is_err());
+        assert!(block_on(h.call(&mut cx)).is_err());
+        assert_eq!(cx.lookups, 1);
+    }
+
+    #[test]
+    fn any_output() {
+        async fn lookup(cx: &mut Context) -> Option<usize> {
+            cx.lookups += 1;
+            Some(cx.token.len())
+        }
+
+        let h = Memo::new(lookup, token);
+
+        let mut cx = Context {
+            token: "valid",
+            ..Default::default()
+        };
+        assert_eq!(block_on(h.call(&mut cx)), Some(5));
+        assert_eq!(block_on(h.call(&mut cx)), Some(5));
+        assert_eq!(cx.lookups, 1);
+    }
+
+    #[test]
+    fn max_len() {
+        let h = Memo::new(validate, token).max_len(1);
+
+        let mut cx = Context {
+            token: "valid",
+            ..Default::default()
+        };
+        block_on(h.call(&mut cx)).unwrap();
+        block_on(h.call(&mut cx)).unwrap();
+        assert_eq!(cx.lookups, 1);
+
+        async fn echo(cx: &mut Context) -> Result<usize, String> {
+            cx.lookups += 1;
+            Ok(cx.token.len())
+        }
+
+        let h = Memo::new(echo, token).max_len(2);
+        for token in ["a", "bb", "ccc", "a", "bb", "ccc"] {
+            cx.token = token;
+            assert_eq!(block_on(h.call(&mut cx)), Ok(token.len()));
+            assert!(h.len() <= 2);
+        }
+    }
+}
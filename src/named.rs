@@ -0,0 +1,74 @@
+use crate::{BoxFuture, Handle};
+
+/// Associates a static name with a handler, overriding [`Handle::name`].
+#[derive(Debug, Clone)]
+pub struct NamedHandle<H> {
+    name: &'static str,
+    h: H,
+}
+
+impl<H> NamedHandle<H> {
+    /// Creates a new [`NamedHandle`].
+    #[inline]
+    pub const fn new(name: &'static str, h: H) -> Self {
+        Self { name, h }
+    }
+
+    /// Returns the name of the handler.
+    #[inline]
+    pub const fn name(&self) -> &'static str {
+        self.name
+    }
+}
+
+impl<'a, Context, H> Handle<'a, Context> for NamedHandle<H>
+where
+    H: Handle<'a, Context>,
+{
+    type Output = H::Output;
+
+    #[inline]
+    fn call(&'a self, cx: &'a mut Context) -> BoxFuture<'a, Self::Output> {
+        self.h.call(cx)
+    }
+
+    #[inline]
+    fn name(&self) -> &str {
+        self.name
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ArcHandle, BoxFuture, Handle, HandleExt, Pipeline};
+    use std::{any::type_name, sync::Arc};
+
+    type Result = anyhow::Result<()>;
+
+    struct Context;
+
+    struct A;
+
+    impl<'a> Handle<'a, Context> for A {
+        type Output = Result;
+
+        fn call(&'a self, _: &'a mut Context) -> BoxFuture<'a, Self::Output> {
+            Box::pin(async { Ok(()) })
+        }
+    }
+
+    #[test]
+    fn names() {
+        assert_eq!(A.name(), type_name::<A>());
+
+        let closure = (|_: &mut Context| async { Ok(()) }).named("closure");
+        assert_eq!(Handle::name(&closure), "closure");
+
+        let h: ArcHandle<Context, Result> = Arc::new(closure);
+        assert_eq!(format!("{:?}", h), r#"Handle("closure")"#);
+
+        let mut pipeline = Pipeline::new();
+        pipeline.push(A).push_arc(h);
+        assert_eq!(pipeline.names(), [type_name::<A>(), "closure"]);
+    }
+}
@@ -0,0 +1,547 @@
+use alloc::{boxed::Box, sync::Arc, vec::Vec};
+use core::{error::Error, fmt, mem, ops::ControlFlow};
+
+#[cfg(feature = "std")]
+use crate::{cancel::Cancel, CancelToken, Cancelled, FromCancelled};
+use crate::{depth::MaxDepth, ArcHandle, BoxFuture, DepthExceeded, Empty, MaybeSend};
+
+#[cfg(feature = "send")]
+pub(crate) type OnNext = Arc<dyn Fn() + Send + Sync>;
+
+#[cfg(not(feature = "send"))]
+pub(crate) type OnNext = Arc<dyn Fn()>;
+
+/// The cursor of a running [`Pipeline`](crate::Pipeline), stored in the context.
+pub struct Next<Context, Output> {
+    handlers: Arc<[ArcHandle<Context, Output>]>,
+    cursor: usize,
+    /// Where the cursor stands when the running handler has not called next yet.
+    caller: usize,
+    stopped: bool,
+    depth: usize,
+    max_depth: Option<MaxDepth<Output>>,
+    #[cfg(feature = "std")]
+    cancel: Option<Cancel<Output>>,
+    /// Called once when the running handler calls next.
+    pub(crate) on_next: Option<OnNext>,
+}
+
+impl<Context, Output> Next<Context, Output> {
+    /// Creates a new [`Next`] starting at the first of the `handlers`.
+    #[inline]
+    pub fn new(handlers: Arc<[ArcHandle<Context, Output>]>) -> Self {
+        Self {
+            handlers,
+            cursor: 0,
+            caller: 0,
+            stopped: false,
+            depth: 0,
+            max_depth: None,
+            #[cfg(feature = "std")]
+            cancel: None,
+            on_next: None,
+        }
+    }
+
+    pub(crate) fn with_max_depth(mut self, max_depth: Option<MaxDepth<Output>>) -> Self {
+        self.max_depth = max_depth;
+        self
+    }
+
+    #[cfg(feature = "std")]
+    pub(crate) fn with_cancel(mut self, token: &CancelToken) -> Self
+    where
+        Output: FromCancelled,
+    {
+        self.cancel = Some((token.clone(), Output::from_cancelled));
+        self
+    }
+
+    /// Continues the depth of an outer cursor, and its maximum depth and
+    /// cancel token when this one has none.
+    pub(crate) fn nest(&mut self, outer: &Self) {
+        self.depth = outer.depth;
+        self.max_depth = self.max_depth.or(outer.max_depth);
+        #[cfg(feature = "std")]
+        if self.cancel.is_none() {
+            self.cancel.clone_from(&outer.cancel);
+        }
+    }
+
+    /// Returns a copy of the cursor, sharing the same handlers.
+    pub(crate) fn duplicate(&self) -> Self {
+        Self {
+            handlers: self.handlers.clone(),
+            cursor: self.cursor,
+            caller: self.caller,
+            stopped: self.stopped,
+            depth: self.depth,
+            max_depth: self.max_depth,
+            #[cfg(feature = "std")]
+            cancel: self.cancel.clone(),
+            on_next: self.on_next.clone(),
+        }
+    }
+
+    /// Returns the number of handlers currently running.
+    #[inline]
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    /// Advances the cursor and returns the handler under it.
+    ///
+    /// Returns `None` once the pipeline has been stopped.
+    pub fn pop(&mut self) -> Option<ArcHandle<Context, Output>> {
+        if self.stopped {
+            return None;
+        }
+        let h = self.handlers.get(self.cursor).cloned();
+        if h.is_some() {
+            self.cursor += 1;
+        }
+        h
+    }
+
+    /// Returns the `n`-th upcoming handler without advancing the cursor, `0`
+    /// being the one the next call to [`ContextExt::next`] runs.
+    ///
+    /// Returns `None` once the pipeline has been stopped.
+    pub fn peek(&self, n: usize) -> Option<ArcHandle<Context, Output>> {
+        self.upcoming().get(n).cloned()
+    }
+
+    /// Returns the number of upcoming handlers, `0` once the pipeline has been
+    /// stopped.
+    pub fn remaining(&self) -> usize {
+        if self.stopped {
+            return 0;
+        }
+        self.handlers.len().saturating_sub(self.cursor)
+    }
+
+    /// Returns the names of the upcoming handlers in order, see
+    /// [`Handle::name`](crate::Handle::name).
+    ///
+    /// The names are borrowed from the handlers, the cursor holds no slice of
+    /// them, so they are collected into a `Vec` rather than returned as a
+    /// `&[&str]`.
+    pub fn peek_names(&self) -> Vec<&str>
+    where
+        Context: 'static,
+        Output: 'static,
+    {
+        self.upcoming().iter().map(|h| h.name()).collect()
+    }
+
+    /// Returns `true` if the running handler has called [`ContextExt::next`].
+    pub(crate) fn called(&self) -> bool {
+        self.cursor != self.caller
+    }
+
+    /// Returns the upcoming handlers, none once the pipeline has been stopped.
+    pub(crate) fn upcoming(&self) -> &[ArcHandle<Context, Output>] {
+        let upcoming = self.handlers.get(self.cursor..).unwrap_or_default();
+        &upcoming[..self.remaining()]
+    }
+
+    /// Advances the cursor past the `n` upcoming handlers without calling them.
+    ///
+    /// The `n` is clamped to the number of handlers left. The skipped handlers
+    /// are bypassed for the rest of this run.
+    pub fn skip(&mut self, n: usize) {
+        let on_caller = self.cursor == self.caller;
+        let n = n.min(self.handlers.len().saturating_sub(self.cursor));
+        self.cursor += n;
+        if on_caller {
+            // Skipping is not calling `next`.
+            self.caller = self.cursor;
+        }
+    }
+
+    /// Puts the cursor back into the context and calls the handler under it.
+    pub fn run(self, cx: &mut Context) -> BoxFuture<'_, Output>
+    where
+        Context: ContextExt<Output>,
+        Output: Empty + FromNextAlreadyCalled + 'static,
+    {
+        *cx.next_mut() = self;
+        cx.next()
+    }
+
+    /// Lets the running handler call [`ContextExt::next`] once more, running
+    /// the rest of the pipeline again.
+    ///
+    /// Calling it twice is a bug reported with [`NextAlreadyCalled`], this is
+    /// the escape hatch for the handlers which legitimately re-run the rest,
+    /// like retries.
+    #[inline]
+    pub fn fused(&mut self) {
+        self.cursor = self.caller;
+    }
+
+    /// Runs the handlers in place of the cursor already in the context, which
+    /// is restored once this run completes.
+    pub(crate) fn run_nested(self, cx: &mut Context) -> BoxFuture<'_, Output>
+    where
+        Context: ContextExt<Output>,
+        Output: Empty + FromNextAlreadyCalled + 'static,
+    {
+        Box::pin(async move {
+            let prev = mem::replace(cx.next_mut(), self);
+            cx.next_mut().nest(&prev);
+            let output = cx.next().await;
+            *cx.next_mut() = prev;
+            output
+        })
+    }
+
+    /// Stops the pipeline, the remaining handlers are skipped.
+    #[inline]
+    pub fn stop(&mut self) {
+        self.stopped = true;
+    }
+
+    /// Returns `true` if the pipeline has been stopped.
+    #[inline]
+    pub fn is_stopped(&self) -> bool {
+        self.stopped
+    }
+}
+
+impl<Context, Output> Default for Next<Context, Output> {
+    fn default() -> Self {
+        Self::new(Arc::default())
+    }
+}
+
+impl<Context, Output> fmt::Debug for Next<Context, Output> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Next")
+            .field("len", &self.handlers.len())
+            .field("cursor", &self.cursor)
+            .field("stopped", &self.stopped)
+            .field("depth", &self.depth)
+            .finish()
+    }
+}
+
+/// The error returned when a handler calls [`ContextExt::next`] a second time
+/// without [`Next::fused`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NextAlreadyCalled;
+
+impl fmt::Display for NextAlreadyCalled {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("`next` called more than once by the same handler")
+    }
+}
+
+impl Error for NextAlreadyCalled {}
+
+/// Lets the outputs with `&'static str` errors carry the error as its message.
+impl From<NextAlreadyCalled> for &'static str {
+    #[inline]
+    fn from(_: NextAlreadyCalled) -> Self {
+        "`next` called more than once by the same handler"
+    }
+}
+
+/// An output which can carry a [`NextAlreadyCalled`] error.
+///
+/// The outputs without an error, such as `()` or [`Option`], return
+/// [`Empty::empty`] instead.
+pub trait FromNextAlreadyCalled {
+    /// Converts the error into the output.
+    fn from_next_already_called(e: NextAlreadyCalled) -> Self;
+}
+
+impl<T, E> FromNextAlreadyCalled for Result<T, E>
+where
+    E: From<NextAlreadyCalled>,
+{
+    #[inline]
+    fn from_next_already_called(e: NextAlreadyCalled) -> Self {
+        Err(e.into())
+    }
+}
+
+impl<B, C> FromNextAlreadyCalled for ControlFlow<B, C>
+where
+    C: FromNextAlreadyCalled,
+{
+    #[inline]
+    fn from_next_already_called(e: NextAlreadyCalled) -> Self {
+        ControlFlow::Continue(C::from_next_already_called(e))
+    }
+}
+
+impl FromNextAlreadyCalled for () {
+    #[inline]
+    fn from_next_already_called(_: NextAlreadyCalled) -> Self {}
+}
+
+impl<T> FromNextAlreadyCalled for Option<T> {
+    #[inline]
+    fn from_next_already_called(_: NextAlreadyCalled) -> Self {
+        None
+    }
+}
+
+impl<T> FromNextAlreadyCalled for Vec<T> {
+    #[inline]
+    fn from_next_already_called(_: NextAlreadyCalled) -> Self {
+        Vec::new()
+    }
+}
+
+/// A context which carries the cursor of a running [`Pipeline`](crate::Pipeline).
+pub trait ContextExt<Output>: Sized + MaybeSend + 'static {
+    /// Returns the cursor of the running pipeline.
+    fn next_mut(&mut self) -> &mut Next<Self, Output>;
+
+    /// Returns the cursor of the running pipeline, read-only.
+    fn next_ref(&self) -> &Next<Self, Output>;
+
+    /// Calls the next handler of the running pipeline.
+    ///
+    /// Returns [`Empty::empty`] when there is no handler left, and the
+    /// [`DepthExceeded`] error when the handlers nest deeper than the maximum
+    /// depth of the pipeline. Once the token of a cancellable run is cancelled,
+    /// returns the [`Cancelled`] error instead of calling the next handler.
+    ///
+    /// A handler calling it a second time without [`Next::fused`] gets the
+    /// [`NextAlreadyCalled`] error, the handlers are never run twice.
+    fn next(&mut self) -> BoxFuture<'_, Output>
+    where
+        Output: Empty + FromNextAlreadyCalled + 'static,
+    {
+        let next = self.next_mut();
+        if next.cursor != next.caller {
+            return Box::pin(async { Output::from_next_already_called(NextAlreadyCalled) });
+        }
+        if let Some(f) = next.on_next.take() {
+            f();
+        }
+        #[cfg(feature = "std")]
+        if let Some((token, f)) = &next.cancel {
+            if token.is_cancelled() {
+                let f = *f;
+                return Box::pin(async move { f(Cancelled) });
+            }
+        }
+        if let Some((max_depth, f)) = next.max_depth {
+            if next.depth >= max_depth {
+                return Box::pin(async move { f(DepthExceeded { max_depth }) });
+            }
+        }
+
+        match next.pop() {
+            Some(h) => {
+                let caller = mem::replace(&mut next.caller, next.cursor);
+                next.depth += 1;
+                Box::pin(async move {
+                    let output = h.call(self).await;
+                    let next = self.next_mut();
+                    next.caller = caller;
+                    next.depth = next.depth.saturating_sub(1);
+                    output
+                })
+            }
+            None => {
+                // Moves past the end, so a second call is caught as well.
+                next.cursor += 1;
+                Box::pin(async { Output::empty() })
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ArcHandle, ContextExt, HandleExt, Next, NextAlreadyCalled, Pipeline};
+    use futures::executor::block_on;
+    use std::sync::Arc;
+
+    type Result = anyhow::Result<()>;
+
+    #[derive(Default)]
+    struct Context {
+        trace: Vec<&'static str>,
+        next: Next<Self, Result>,
+    }
+
+    impl ContextExt<Result> for Context {
+        fn next_mut(&mut self) -> &mut Next<Self, Result> {
+            &mut self.next
+        }
+
+        fn next_ref(&self) -> &Next<Self, Result> {
+            &self.next
+        }
+    }
+
+    async fn twice(cx: &mut Context) -> Result {
+        cx.trace.push("twice");
+        cx.next().await?;
+        cx.next().await
+    }
+
+    async fn retry(cx: &mut Context) -> Result {
+        cx.trace.push("retry");
+        cx.next().await?;
+        cx.next_mut().fused();
+        cx.next().await
+    }
+
+    async fn handler(cx: &mut Context) -> Result {
+        cx.trace.push("handler");
+        cx.next().await
+    }
+
+    async fn admin(cx: &mut Context) -> Result {
+        cx.trace.push("admin");
+        cx.next().await
+    }
+
+    // Skips the `admin` handler when it is up next.
+    async fn route(cx: &mut Context) -> Result {
+        cx.trace.push("route");
+        let name = cx.next.peek(0).map(|h| h.name().to_string());
+        if name.is_some_and(|name| name.ends_with("admin")) {
+            cx.next_mut().skip(1);
+        }
+        cx.next().await
+    }
+
+    #[test]
+    fn called_twice() {
+        let mut pipeline = Pipeline::new();
+        pipeline.push(twice).push(handler);
+
+        let mut cx = Context::default();
+        let e = block_on(pipeline.run(&mut cx)).unwrap_err();
+        assert_eq!(e.downcast_ref(), Some(&NextAlreadyCalled));
+        assert_eq!(cx.trace, ["twice", "handler"]);
+    }
+
+    #[test]
+    fn last_called_twice() {
+        let mut pipeline = Pipeline::new();
+        pipeline.push(handler).push(twice);
+
+        let mut cx = Context::default();
+        let e = block_on(pipeline.run(&mut cx)).unwrap_err();
+        assert_eq!(e.downcast_ref(), Some(&NextAlreadyCalled));
+        assert_eq!(cx.trace, ["handler", "twice"]);
+    }
+
+    async fn tail(cx: &mut Context) -> Result {
+        cx.trace.push("tail");
+        Ok(())
+    }
+
+    #[test]
+    fn shared_cursor() {
+        // The handlers of a combinator run on the same cursor.
+        let mut pipeline = Pipeline::new();
+        pipeline.push(handler.chain(handler)).push(tail);
+
+        let mut cx = Context::default();
+        let e = block_on(pipeline.run(&mut cx)).unwrap_err();
+        assert_eq!(e.downcast_ref(), Some(&NextAlreadyCalled));
+        assert_eq!(cx.trace, ["handler", "tail", "handler"]);
+
+        let mut pipeline = Pipeline::new();
+        pipeline.push((handler, handler)).push(tail);
+
+        let mut cx = Context::default();
+        let e = block_on(pipeline.run(&mut cx)).unwrap_err();
+        assert_eq!(e.downcast_ref(), Some(&NextAlreadyCalled));
+        assert_eq!(cx.trace, ["handler", "tail", "handler"]);
+    }
+
+    #[test]
+    fn fused() {
+        let mut pipeline = Pipeline::new();
+        pipeline.push(retry).push(handler).push(handler);
+
+        let mut cx = Context::default();
+        assert!(block_on(pipeline.run(&mut cx)).is_ok());
+        assert_eq!(
+            cx.trace,
+            ["retry", "handler", "handler", "handler", "handler"]
+        );
+    }
+
+    async fn depth(cx: &mut Context) -> Result {
+        let remaining = cx.next.remaining();
+        assert_eq!(cx.next.peek_names().len(), remaining);
+        cx.trace.push(["none", "one", "two", "three"][remaining]);
+        cx.next().await
+    }
+
+    #[test]
+    fn remaining() {
+        let mut pipeline = Pipeline::new();
+        pipeline.push(depth).push(depth).push(depth);
+
+        let mut cx = Context::default();
+        assert!(block_on(pipeline.run(&mut cx)).is_ok());
+        assert_eq!(cx.trace, ["two", "one", "none"]);
+
+        let mut next = Next::<Context, Result>::new(
+            vec![Arc::new(admin) as ArcHandle<_, _>, Arc::new(handler)].into(),
+        );
+        assert_eq!(next.remaining(), 2);
+        assert!(next.peek_names()[0].ends_with("admin"));
+        next.skip(1);
+        assert!(next.peek_names()[0].ends_with("handler"));
+        next.stop();
+        assert_eq!(next.remaining(), 0);
+        assert!(next.peek_names().is_empty());
+    }
+
+    #[test]
+    fn skip_and_peek() {
+        let mut pipeline = Pipeline::new();
+        pipeline.push(route).push(admin).push(handler);
+
+        let mut cx = Context::default();
+        assert!(block_on(pipeline.run(&mut cx)).is_ok());
+        assert_eq!(cx.trace, ["route", "handler"]);
+
+        let mut pipeline = Pipeline::new();
+        pipeline.push(route).push(handler).push(admin);
+
+        let mut cx = Context::default();
+        assert!(block_on(pipeline.run(&mut cx)).is_ok());
+        assert_eq!(cx.trace, ["route", "handler", "admin"]);
+    }
+
+    #[test]
+    fn skip_clamps() {
+        let mut pipeline = Pipeline::new();
+        pipeline.push(handler).push(admin);
+        let mut next = Next::new(pipeline.iter().cloned().collect());
+
+        assert!(next.peek(1).is_some());
+        assert!(next.peek(2).is_none());
+        assert!(next.peek(usize::MAX).is_none());
+        next.skip(usize::MAX);
+        assert!(next.peek(0).is_none());
+        assert!(next.pop().is_none());
+    }
+
+    #[test]
+    fn peek_stopped() {
+        let mut pipeline = Pipeline::new();
+        pipeline.push(handler).push(admin);
+        let mut next = Next::new(pipeline.iter().cloned().collect());
+
+        assert!(next.peek(0).is_some());
+        next.stop();
+        assert!(next.peek(0).is_none());
+        assert!(next.peek_names().is_empty());
+    }
+}
@@ -0,0 +1,141 @@
+use alloc::boxed::Box;
+use core::future::Future;
+#[cfg(feature = "std")]
+use std::sync::{Mutex, PoisonError};
+
+#[cfg(feature = "std")]
+use crate::Handle;
+use crate::{BoxFuture, MaybeSend};
+
+/// A handler which consumes itself when called, so it runs at most once.
+///
+/// It fills the gap where [`Handle`] borrows `&self` but the handler must own
+/// its state, e.g. a transaction committed exactly once.
+pub trait HandleOnce<'a, Context>: MaybeSend + 'static {
+    /// The returned type after the call operator is used.
+    type Output;
+
+    /// Invokes the handler within the given `Context`, consuming it.
+    fn call_once(self, cx: &'a mut Context) -> BoxFuture<'a, Self::Output>;
+}
+
+impl<'a, Context, F, Fut> HandleOnce<'a, Context> for F
+where
+    F: FnOnce(&'a mut Context) -> Fut + MaybeSend + 'static,
+    Fut: Future + MaybeSend + 'a,
+    Context: 'a,
+{
+    type Output = Fut::Output;
+
+    fn call_once(self, cx: &'a mut Context) -> BoxFuture<'a, Self::Output> {
+        Box::pin((self)(cx))
+    }
+}
+
+/// Runs a [`HandleOnce`] as a [`Handle`].
+///
+/// # Panics
+///
+/// Panics when called a second time.
+#[cfg(feature = "std")]
+#[derive(Debug)]
+pub struct OnceWrapper<H> {
+    h: Mutex<Option<H>>,
+}
+
+#[cfg(feature = "std")]
+impl<H> OnceWrapper<H> {
+    /// Creates a new [`OnceWrapper`].
+    #[inline]
+    pub const fn new(h: H) -> Self {
+        Self {
+            h: Mutex::new(Some(h)),
+        }
+    }
+
+    /// Returns `true` if the handler has already been called.
+    pub fn is_called(&self) -> bool {
+        self.h
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .is_none()
+    }
+}
+
+#[cfg(feature = "std")]
+impl<'a, Context, H> Handle<'a, Context> for OnceWrapper<H>
+where
+    H: HandleOnce<'a, Context>,
+{
+    type Output = H::Output;
+
+    fn call(&'a self, cx: &'a mut Context) -> BoxFuture<'a, Self::Output> {
+        let h = self
+            .h
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .take()
+            .expect("`OnceWrapper` called more than once");
+        h.call_once(cx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{BoxFuture, Handle, HandleOnce, OnceWrapper};
+    use futures::executor::block_on;
+
+    #[derive(Default)]
+    struct Context {
+        committed: Vec<String>,
+    }
+
+    struct Transaction {
+        writes: Vec<String>,
+    }
+
+    impl<'a> HandleOnce<'a, Context> for Transaction {
+        type Output = usize;
+
+        fn call_once(self, cx: &'a mut Context) -> BoxFuture<'a, Self::Output> {
+            Box::pin(async move {
+                let n = self.writes.len();
+                cx.committed.extend(self.writes);
+                n
+            })
+        }
+    }
+
+    fn transaction() -> OnceWrapper<Transaction> {
+        OnceWrapper::new(Transaction {
+            writes: vec!["a".to_string(), "b".to_string()],
+        })
+    }
+
+    #[test]
+    fn call_once() {
+        let mut cx = Context::default();
+        let h = transaction();
+        assert!(!h.is_called());
+        assert_eq!(block_on(h.call(&mut cx)), 2);
+        assert!(h.is_called());
+        assert_eq!(cx.committed, ["a", "b"]);
+
+        let greeting = String::from("hi");
+        let h = OnceWrapper::new(move |cx: &mut Context| {
+            cx.committed.push(greeting);
+            async {}
+        });
+        block_on(h.call(&mut cx));
+        assert_eq!(cx.committed, ["a", "b", "hi"]);
+    }
+
+    #[test]
+    #[should_panic(expected = "`OnceWrapper` called more than once")]
+    fn called_twice() {
+        let mut cx = Context::default();
+        let h = transaction();
+        block_on(h.call(&mut cx));
+        block_on(h.call(&mut cx));
+    }
+}
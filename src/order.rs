@@ -0,0 +1,319 @@
+use alloc::{
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::{
+    any::{type_name, TypeId},
+    error::Error,
+    fmt,
+};
+
+use crate::{ArcHandle, BoxFuture, Handle, IntoHandle};
+
+/// Declares where a handler must run relative to other handlers, checked by
+/// [`validate_pipeline`].
+///
+/// With the `macros` feature it can be derived, listing the types with the
+/// `handler` attribute:
+///
+/// ```ignore
+/// #[derive(handle::HandlerMeta)]
+/// #[handler(before = [Router], after = [Logger])]
+/// struct Auth;
+/// ```
+pub trait HandlerMeta: 'static {
+    /// Returns the name reported in the violations, the type name by default.
+    fn name() -> &'static str {
+        type_name::<Self>()
+    }
+
+    /// Returns the types of the handlers which must run after this one.
+    fn must_run_before() -> &'static [TypeId] {
+        &[]
+    }
+
+    /// Returns the types of the handlers which must run before this one.
+    fn must_run_after() -> &'static [TypeId] {
+        &[]
+    }
+}
+
+/// The ordering constraints of a handler, held by [`Ordered`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Meta {
+    type_id: TypeId,
+    name: &'static str,
+    before: &'static [TypeId],
+    after: &'static [TypeId],
+}
+
+impl Meta {
+    /// Returns the constraints declared by `H`.
+    pub fn of<H>() -> Self
+    where
+        H: HandlerMeta,
+    {
+        Self {
+            type_id: TypeId::of::<H>(),
+            name: H::name(),
+            before: H::must_run_before(),
+            after: H::must_run_after(),
+        }
+    }
+
+    /// Returns the type of the handler.
+    #[inline]
+    pub const fn type_id(&self) -> TypeId {
+        self.type_id
+    }
+
+    /// Returns the name of the handler.
+    #[inline]
+    pub const fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// Returns the types of the handlers which must run after this one.
+    #[inline]
+    pub const fn must_run_before(&self) -> &'static [TypeId] {
+        self.before
+    }
+
+    /// Returns the types of the handlers which must run before this one.
+    #[inline]
+    pub const fn must_run_after(&self) -> &'static [TypeId] {
+        self.after
+    }
+}
+
+/// A handler with the constraints of its [`HandlerMeta`], the only ones
+/// [`validate_pipeline`] checks.
+///
+/// The constraints of a handler pushed without it are not checked, only the
+/// ones of the other handlers naming its type. It must be the outermost
+/// adapter, since a handler wrapping it hides it from [`validate_pipeline`].
+pub struct Ordered<Context, Output> {
+    meta: Meta,
+    h: ArcHandle<Context, Output>,
+}
+
+impl<Context, Output> Ordered<Context, Output> {
+    /// Creates a new [`Ordered`].
+    #[inline]
+    pub fn new<H, K>(h: H) -> Self
+    where
+        H: IntoHandle<Context, Output, K> + HandlerMeta,
+    {
+        Self {
+            meta: Meta::of::<H>(),
+            h: h.into_handle(),
+        }
+    }
+
+    /// Returns the constraints of the handler.
+    #[inline]
+    pub const fn meta(&self) -> Meta {
+        self.meta
+    }
+
+    /// Returns the handler.
+    #[inline]
+    pub fn into_inner(self) -> ArcHandle<Context, Output> {
+        self.h
+    }
+}
+
+impl<'a, Context, Output> Handle<'a, Context> for Ordered<Context, Output>
+where
+    Context: 'static,
+    Output: 'static,
+{
+    type Output = Output;
+
+    #[inline]
+    fn call(&'a self, cx: &'a mut Context) -> BoxFuture<'a, Self::Output> {
+        self.h.call(cx)
+    }
+
+    #[inline]
+    fn name(&self) -> &str {
+        self.h.name()
+    }
+}
+
+impl<Context, Output> Clone for Ordered<Context, Output> {
+    fn clone(&self) -> Self {
+        Self {
+            meta: self.meta,
+            h: self.h.clone(),
+        }
+    }
+}
+
+impl<Context, Output> fmt::Debug for Ordered<Context, Output>
+where
+    Context: 'static,
+    Output: 'static,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Ordered")
+            .field("meta", &self.meta)
+            .field("h", &self.h)
+            .finish()
+    }
+}
+
+/// A constraint broken by the order of a pipeline: `before` must run before
+/// `after`, but runs after it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OrderViolation {
+    /// The name of the handler which must run first.
+    pub before: String,
+    /// The name of the handler which must run second.
+    pub after: String,
+}
+
+impl fmt::Display for OrderViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "`{}` must run before `{}`", self.before, self.after)
+    }
+}
+
+impl Error for OrderViolation {}
+
+/// Checks the order of the handlers against the constraints of the [`Ordered`]
+/// ones, returning every broken constraint once.
+///
+/// A handler is identified by the type of its [`Meta`] when it is [`Ordered`],
+/// or by its own type. A constraint naming a handler which is not in the list
+/// holds, and a handler present several times must run entirely before or
+/// after the other one.
+pub fn validate_pipeline<Context, Output>(
+    handlers: &[ArcHandle<Context, Output>],
+) -> Result<(), Vec<OrderViolation>>
+where
+    Context: 'static,
+    Output: 'static,
+{
+    let entries: Vec<_> = handlers
+        .iter()
+        .map(|h| {
+            let meta = h
+                .downcast_ref::<Ordered<Context, Output>>()
+                .map(Ordered::meta);
+            let id = meta.map_or_else(|| (**h).as_any().type_id(), |m| m.type_id());
+            (id, meta, meta.map_or_else(|| h.name(), |m| m.name()))
+        })
+        .collect();
+
+    let mut checked = Vec::new();
+    let mut violations = Vec::new();
+    let mut check = |before: TypeId, after: TypeId| {
+        if checked.contains(&(before, after)) {
+            return;
+        }
+        checked.push((before, after));
+        let last = entries.iter().rposition(|(id, ..)| *id == before);
+        let first = entries.iter().position(|(id, ..)| *id == after);
+        if let (Some(last), Some(first)) = (last, first) {
+            if last > first {
+                violations.push(OrderViolation {
+                    before: entries[last].2.to_string(),
+                    after: entries[first].2.to_string(),
+                });
+            }
+        }
+    };
+
+    for (id, meta, _) in &entries {
+        let Some(meta) = meta else { continue };
+        for &other in meta.must_run_before() {
+            check(*id, other);
+        }
+        for &other in meta.must_run_after() {
+            check(other, *id);
+        }
+    }
+
+    if violations.is_empty() {
+        Ok(())
+    } else {
+        Err(violations)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{BoxFuture, Handle, HandleExt, HandlerMeta, OrderViolation, Pipeline};
+    use std::any::TypeId;
+
+    type Result = anyhow::Result<()>;
+
+    struct Context;
+
+    macro_rules! handlers {
+        ($($name:ident),*) => {$(
+            struct $name;
+
+            impl<'a> Handle<'a, Context> for $name {
+                type Output = Result;
+
+                fn call(&'a self, _: &'a mut Context) -> BoxFuture<'a, Self::Output> {
+                    Box::pin(async { Ok(()) })
+                }
+            }
+        )*};
+    }
+
+    handlers!(Log, Auth, Route);
+
+    impl HandlerMeta for Auth {
+        fn name() -> &'static str {
+            "auth"
+        }
+
+        fn must_run_before() -> &'static [TypeId] {
+            const BEFORE: &[TypeId] = &[TypeId::of::<Route>()];
+            BEFORE
+        }
+
+        fn must_run_after() -> &'static [TypeId] {
+            const AFTER: &[TypeId] = &[TypeId::of::<Log>()];
+            AFTER
+        }
+    }
+
+    fn validate(pipeline: &Pipeline<Context, Result>) -> std::result::Result<(), Vec<String>> {
+        pipeline
+            .validate()
+            .map_err(|v| v.iter().map(OrderViolation::to_string).collect())
+    }
+
+    #[test]
+    fn valid() {
+        let mut pipeline = Pipeline::new();
+        pipeline.push(Log).push(Auth.ordered()).push(Route);
+        assert_eq!(validate(&pipeline), Ok(()));
+
+        // A constraint on a missing handler holds.
+        let mut pipeline = Pipeline::new();
+        pipeline.push(Auth.ordered()).push(Route);
+        assert_eq!(validate(&pipeline), Ok(()));
+    }
+
+    #[test]
+    fn violations() {
+        let mut pipeline = Pipeline::new();
+        pipeline.push(Route).push(Auth.ordered()).push(Log);
+
+        let route = std::any::type_name::<Route>();
+        let log = std::any::type_name::<Log>();
+        assert_eq!(
+            validate(&pipeline),
+            Err(vec![
+                format!("`auth` must run before `{route}`"),
+                format!("`{log}` must run before `auth`"),
+            ])
+        );
+    }
+}
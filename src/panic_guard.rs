@@ -0,0 +1,110 @@
+use std::{
+    any::Any,
+    future::poll_fn,
+    panic::{catch_unwind, AssertUnwindSafe},
+    task::Poll,
+};
+
+use crate::{BoxFuture, Handle, MaybeSend, MaybeSync};
+
+/// Catches the panics of the handler and recovers them with a fallback output.
+///
+/// The inner future is polled under [`catch_unwind`], so a panic raised while
+/// the handler is polled, before or after any `.await`, is passed to the
+/// fallback as its payload. Panics in tasks spawned by the handler unwind on
+/// their own executor and are not caught, and nothing is caught when the
+/// binary is built with `panic = "abort"`.
+///
+/// The context may be left half-updated by the panicking handler.
+#[derive(Debug, Clone)]
+pub struct PanicGuard<H, F> {
+    h: H,
+    f: F,
+}
+
+impl<H, F> PanicGuard<H, F> {
+    /// Creates a new [`PanicGuard`].
+    #[inline]
+    pub const fn new(h: H, f: F) -> Self {
+        Self { h, f }
+    }
+}
+
+impl<'a, Context, H, F> Handle<'a, Context> for PanicGuard<H, F>
+where
+    H: Handle<'a, Context>,
+    F: Fn(Box<dyn Any + Send>) -> H::Output + MaybeSend + MaybeSync + 'static,
+    Context: MaybeSend + 'a,
+    H::Output: 'a,
+{
+    type Output = H::Output;
+
+    fn call(&'a self, cx: &'a mut Context) -> BoxFuture<'a, Self::Output> {
+        let mut cx = Some(cx);
+        let mut fut = None;
+
+        Box::pin(poll_fn(move |task| {
+            // The handler is called on the first poll, so a panic before its
+            // first `.await` is caught as well.
+            catch_unwind(AssertUnwindSafe(|| {
+                fut.get_or_insert_with(|| self.h.call(cx.take().expect("polled after completion")))
+                    .as_mut()
+                    .poll(task)
+            }))
+            .unwrap_or_else(|payload| Poll::Ready((self.f)(payload)))
+        }))
+    }
+
+    #[inline]
+    fn name(&self) -> &str {
+        self.h.name()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Handle, PanicGuard};
+    use async_std::task::yield_now;
+    use std::any::Any;
+
+    #[derive(Default)]
+    struct Context {
+        steps: usize,
+    }
+
+    async fn step(cx: &mut Context) -> Result<usize, String> {
+        cx.steps += 1;
+        yield_now().await;
+        cx.steps += 1;
+        Ok(cx.steps)
+    }
+
+    async fn boom(cx: &mut Context) -> Result<usize, String> {
+        cx.steps += 1;
+        yield_now().await;
+        if cx.steps > 0 {
+            panic!("boom after {} steps", cx.steps);
+        }
+        Ok(cx.steps)
+    }
+
+    fn recover(payload: Box<dyn Any + Send>) -> Result<usize, String> {
+        Err(payload
+            .downcast::<String>()
+            .map_or_else(|_| "unknown".to_string(), |msg| *msg))
+    }
+
+    #[async_std::test]
+    async fn passes_through() {
+        let mut cx = Context::default();
+        assert_eq!(PanicGuard::new(step, recover).call(&mut cx).await, Ok(2));
+    }
+
+    #[async_std::test]
+    async fn panic_at_await() {
+        let mut cx = Context::default();
+        let output = PanicGuard::new(boom, recover).call(&mut cx).await;
+        assert_eq!(output, Err("boom after 1 steps".to_string()));
+        assert_eq!(cx.steps, 1);
+    }
+}
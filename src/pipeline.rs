@@ -0,0 +1,904 @@
+use alloc::{boxed::Box, sync::Arc, vec::Vec};
+use core::{any::TypeId, fmt, mem};
+
+use crate::{
+    depth::MaxDepth, hooks::Hooks, validate_pipeline, ArcHandle, BoxFuture, ContextExt, Empty,
+    FromDepthExceeded, FromNextAlreadyCalled, HandleGroup, IntoHandle, MaybeSend, MaybeSync, Next,
+    OrderViolation, Stack, WeakHandle,
+};
+#[cfg(feature = "std")]
+use crate::{CancelToken, FromCancelled};
+
+/// The storage of the handlers of a [`Pipeline`], sorted by priority descending.
+#[cfg(not(feature = "handle-smallvec"))]
+type Handlers<Context, Output> = Vec<(i32, ArcHandle<Context, Output>)>;
+
+/// The storage of the handlers of a [`Pipeline`], sorted by priority descending.
+///
+/// Up to 8 handlers are stored inline. Each entry is an `i32` priority and an
+/// [`ArcHandle`], a pointer to a trait object of two pointer widths; with the
+/// padding an entry takes three pointer widths, so the inline storage takes 24
+/// pointer widths: 192 bytes on 64-bit targets, 96 bytes on 32-bit targets.
+#[cfg(feature = "handle-smallvec")]
+type Handlers<Context, Output> = smallvec::SmallVec<[(i32, ArcHandle<Context, Output>); 8]>;
+
+/// The order in which the handlers of the same priority run, see
+/// [`Pipeline::with_order`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Order {
+    /// The handlers run in the order they were pushed.
+    #[default]
+    Fifo,
+    /// The handlers run in the reverse of the order they were pushed, like a
+    /// stack of handlers popped from a `Vec`.
+    Lifo,
+}
+
+/// An ordered list of handlers running on the same context.
+///
+/// Handlers with a higher priority are called first, handlers with the same
+/// priority are called in the order they were pushed, or in the reverse order
+/// with [`Order::Lifo`]. Each one continues the
+/// pipeline by calling [`ContextExt::next`].
+///
+/// With the `handle-smallvec` feature, pipelines of up to 8 handlers are
+/// stored inline without a heap allocation. This only saves the allocation
+/// of the storage while registering: each [`Pipeline::run`] still copies the
+/// handlers into a new `Arc<[_]>` for the cursor, one allocation and one
+/// reference count increment per handler. Run a [`Stack`] from
+/// [`Pipeline::freeze`] on hot paths instead, which shares its handlers
+/// across runs.
+pub struct Pipeline<Context, Output> {
+    handlers: Handlers<Context, Output>,
+    max_depth: Option<MaxDepth<Output>>,
+    hooks: Arc<Hooks<Context, Output>>,
+    dedup: bool,
+    order: Order,
+}
+
+impl<Context, Output> Pipeline<Context, Output> {
+    /// Creates an empty [`Pipeline`], running the handlers in push order.
+    #[inline]
+    pub fn new() -> Self {
+        Self::with_order(Order::Fifo)
+    }
+
+    /// Creates an empty [`Pipeline`] running the handlers of the same priority
+    /// in the given `order`.
+    #[inline]
+    pub fn with_order(order: Order) -> Self {
+        Self {
+            handlers: Handlers::new(),
+            max_depth: None,
+            hooks: Arc::default(),
+            dedup: false,
+            order,
+        }
+    }
+
+    /// Returns the order of the handlers of the same priority.
+    #[inline]
+    pub fn order(&self) -> Order {
+        self.order
+    }
+
+    /// Limits how deep the handlers can nest within a single run.
+    ///
+    /// Once `depth` handlers are running, [`ContextExt::next`] returns the
+    /// [`DepthExceeded`](crate::DepthExceeded) error instead of calling the next
+    /// handler, so a re-entrant handler fails instead of overflowing the stack.
+    /// The depth is tracked by the cursor in the context, concurrent runs do not
+    /// share it. Nested pipelines continue the depth of the outer one.
+    pub fn max_depth(&mut self, depth: usize) -> &mut Self
+    where
+        Output: FromDepthExceeded,
+    {
+        self.max_depth = Some((depth, Output::from_depth_exceeded));
+        self
+    }
+
+    pub(crate) fn get_max_depth(&self) -> Option<MaxDepth<Output>> {
+        self.max_depth
+    }
+
+    /// Registers a hook called with the context before the first handler runs.
+    ///
+    /// The hooks run in the order they were registered. With the `std`
+    /// feature, a panicking hook is caught and the pipeline goes on.
+    pub fn on_enter<F>(&mut self, f: F) -> &mut Self
+    where
+        F: Fn(&Context) + MaybeSend + MaybeSync + 'static,
+    {
+        Arc::make_mut(&mut self.hooks).enter.push(Arc::new(f));
+        self
+    }
+
+    /// Registers a hook called with the context and the output once the
+    /// handlers complete.
+    ///
+    /// The hooks run in the order they were registered. With the `std`
+    /// feature, a panicking hook is caught and the next hooks still run.
+    pub fn on_exit<F>(&mut self, f: F) -> &mut Self
+    where
+        F: Fn(&Context, &Output) + MaybeSend + MaybeSync + 'static,
+    {
+        Arc::make_mut(&mut self.hooks).exit.push(Arc::new(f));
+        self
+    }
+
+    pub(crate) fn get_hooks(&self) -> &Arc<Hooks<Context, Output>> {
+        &self.hooks
+    }
+
+    /// Appends a handler with the default priority `0`.
+    pub fn push<H, K>(&mut self, h: H) -> &mut Self
+    where
+        H: IntoHandle<Context, Output, K>,
+    {
+        self.push_with_priority(h, 0)
+    }
+
+    /// Appends a closure with the default priority `0`.
+    ///
+    /// A closure cannot return a future borrowing its argument, so it returns
+    /// the future boxed instead; the bound lets the compiler infer the closure
+    /// signature for every lifetime of the context.
+    pub fn push_fn<F>(&mut self, f: F) -> &mut Self
+    where
+        F: for<'a> Fn(&'a mut Context) -> BoxFuture<'a, Output> + MaybeSend + MaybeSync + 'static,
+        Context: 'static,
+        Output: 'static,
+    {
+        self.push(f)
+    }
+
+    /// Inserts a handler after all the handlers with a higher or equal priority,
+    /// or with a higher priority only with [`Order::Lifo`].
+    pub fn push_with_priority<H, K>(&mut self, h: H, priority: i32) -> &mut Self
+    where
+        H: IntoHandle<Context, Output, K>,
+    {
+        self.insert_arc(priority, h.into_handle())
+    }
+
+    /// Inserts a handler where lower priorities run earlier, handlers with the
+    /// same priority run in the order they were inserted, or in the reverse
+    /// order with [`Order::Lifo`].
+    ///
+    /// It is the reverse of [`Pipeline::push_with_priority`]: the priority `p`
+    /// here stands for `!p` there, so `i32::MIN` runs before everything else.
+    pub fn insert_with_priority<H, K>(&mut self, priority: i32, h: H) -> &mut Self
+    where
+        H: IntoHandle<Context, Output, K>,
+    {
+        self.insert_arc(!priority, h.into_handle())
+    }
+
+    /// Inserts a handler in front of all the others, sharing the priority of
+    /// the current first one.
+    pub fn push_first<H, K>(&mut self, h: H) -> &mut Self
+    where
+        H: IntoHandle<Context, Output, K>,
+    {
+        let h = h.into_handle();
+        if !self.is_duplicate(&h) {
+            let priority = self.handlers.first().map_or(0, |(p, _)| *p);
+            self.handlers.insert(0, (priority, h));
+        }
+        self
+    }
+
+    /// Appends a handler behind all the others, sharing the priority of the
+    /// current last one.
+    pub fn push_last<H, K>(&mut self, h: H) -> &mut Self
+    where
+        H: IntoHandle<Context, Output, K>,
+    {
+        let h = h.into_handle();
+        if !self.is_duplicate(&h) {
+            let priority = self.handlers.last().map_or(0, |(p, _)| *p);
+            self.handlers.push((priority, h));
+        }
+        self
+    }
+
+    /// Appends a handler behind all the others, like [`Pipeline::push_last`].
+    #[inline]
+    pub fn append<H, K>(&mut self, h: H) -> &mut Self
+    where
+        H: IntoHandle<Context, Output, K>,
+    {
+        self.push_last(h)
+    }
+
+    /// Moves the handlers of `other` into the pipeline, behind the handlers of
+    /// the same priority.
+    ///
+    /// Both pipelines keep their own order, and with equal priorities all the
+    /// handlers of this pipeline run first. The handlers are moved as they
+    /// are, the hooks and the maximum depth of `other` are dropped.
+    pub fn extend(&mut self, other: Self) -> &mut Self {
+        let handlers = mem::take(&mut self.handlers);
+        self.merge(handlers, other.handlers)
+    }
+
+    /// Moves the handlers of `other` into the pipeline, in front of the
+    /// handlers of the same priority.
+    ///
+    /// Like [`Pipeline::extend`], except that with equal priorities all the
+    /// handlers of `other` run first.
+    pub fn prepend(&mut self, other: Self) -> &mut Self {
+        let handlers = mem::take(&mut self.handlers);
+        self.merge(other.handlers, handlers)
+    }
+
+    /// Merges two lists sorted by priority, `first` winning the ties.
+    fn merge(
+        &mut self,
+        first: Handlers<Context, Output>,
+        second: Handlers<Context, Output>,
+    ) -> &mut Self {
+        let mut first = first.into_iter().peekable();
+        let mut second = second.into_iter().peekable();
+        loop {
+            let take_first = match (first.peek(), second.peek()) {
+                (Some((a, _)), Some((b, _))) => a >= b,
+                (first, _) => first.is_some(),
+            };
+            let next = if take_first {
+                first.next()
+            } else {
+                second.next()
+            };
+            let Some((priority, h)) = next else { break };
+            if !self.is_duplicate(&h) {
+                self.handlers.push((priority, h));
+            }
+        }
+        self
+    }
+
+    /// Appends a handler unless a handler of the same type is already in the
+    /// pipeline, in which case the handler is given back.
+    ///
+    /// The types are compared as pushed, a wrapper like
+    /// [`NamedHandle`](crate::NamedHandle) has a type of its own, but an
+    /// [`ArcHandle`] or a [`BoxHandle`](crate::BoxHandle) is compared by the
+    /// handler inside. Every closure has a distinct type, so two closures never
+    /// collide.
+    pub fn push_unique<H, K>(&mut self, h: H) -> Result<&mut Self, H>
+    where
+        H: IntoHandle<Context, Output, K>,
+    {
+        if self.contains_type(h.handler_type_id()) {
+            return Err(h);
+        }
+        Ok(self.push(h))
+    }
+
+    /// Skips every handler pushed from now on whose type is already in the
+    /// pipeline, like [`Pipeline::push_unique`], and drops the duplicates
+    /// already pushed, keeping the first of each type.
+    pub fn dedup_by_type(&mut self) -> &mut Self {
+        self.dedup = true;
+        let mut seen = Vec::with_capacity(self.handlers.len());
+        self.handlers.retain(|(_, h)| {
+            let id = type_id(h);
+            let unique = !seen.contains(&id);
+            seen.push(id);
+            unique
+        });
+        self
+    }
+
+    /// Removes the [`WeakHandle`]s whose handler has been dropped, returning
+    /// how many were removed.
+    pub fn prune(&mut self) -> usize
+    where
+        Context: 'static,
+        Output: 'static,
+    {
+        let len = self.handlers.len();
+        self.handlers.retain(|(_, h)| {
+            (**h)
+                .as_any()
+                .downcast_ref::<WeakHandle<Context, Output>>()
+                .is_none_or(WeakHandle::is_alive)
+        });
+        len - self.handlers.len()
+    }
+
+    /// Returns `true` if a handler of type `H` is in the pipeline.
+    pub fn contains<H>(&self) -> bool
+    where
+        H: 'static,
+    {
+        self.contains_type(TypeId::of::<H>())
+    }
+
+    fn contains_type(&self, id: TypeId) -> bool {
+        self.iter().any(|h| type_id(h) == id)
+    }
+
+    fn is_duplicate(&self, h: &ArcHandle<Context, Output>) -> bool {
+        self.dedup && self.contains_type(type_id(h))
+    }
+
+    /// Appends a group of handlers, which runs as a single handler.
+    pub fn add_group(&mut self, group: HandleGroup<Context, Output>) -> &mut Self
+    where
+        Context: ContextExt<Output>,
+        Output: Empty + FromNextAlreadyCalled + 'static,
+    {
+        self.push(group)
+    }
+
+    #[cfg(feature = "std")]
+    pub(crate) fn push_arc(&mut self, h: ArcHandle<Context, Output>) -> &mut Self {
+        self.insert_arc(0, h)
+    }
+
+    fn insert_arc(&mut self, priority: i32, h: ArcHandle<Context, Output>) -> &mut Self {
+        if self.is_duplicate(&h) {
+            return self;
+        }
+        let index = match self.order {
+            Order::Fifo => self.handlers.partition_point(|(p, _)| *p >= priority),
+            Order::Lifo => self.handlers.partition_point(|(p, _)| *p > priority),
+        };
+        self.handlers.insert(index, (priority, h));
+        self
+    }
+
+    /// Returns the number of handlers in the pipeline.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.handlers.len()
+    }
+
+    /// Returns `true` if the pipeline has no handlers.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.handlers.is_empty()
+    }
+
+    /// Returns the names of the handlers in order.
+    pub fn names(&self) -> Vec<&str>
+    where
+        Context: 'static,
+        Output: 'static,
+    {
+        self.iter().map(|h| h.name()).collect()
+    }
+
+    /// Returns the handlers in execution order.
+    pub fn iter(
+        &self,
+    ) -> impl DoubleEndedIterator<Item = &ArcHandle<Context, Output>> + ExactSizeIterator {
+        self.handlers.iter().map(|(_, h)| h)
+    }
+
+    /// Checks the order of the handlers against their constraints, see
+    /// [`validate_pipeline`].
+    pub fn validate(&self) -> Result<(), Vec<OrderViolation>>
+    where
+        Context: 'static,
+        Output: 'static,
+    {
+        let handlers: Vec<_> = self.iter().cloned().collect();
+        validate_pipeline(&handlers)
+    }
+
+    /// Freezes the handlers into a [`Stack`], cheap to run many times.
+    #[inline]
+    pub fn freeze(&self) -> Stack<Context, Output> {
+        Stack::from(self)
+    }
+
+    /// Finishes the registration, freezing the handlers in their execution
+    /// order, like [`Pipeline::freeze`] but consuming the pipeline.
+    #[inline]
+    pub fn sorted(self) -> Stack<Context, Output> {
+        Stack::from(self)
+    }
+
+    /// Runs the pipeline on the context.
+    ///
+    /// The handlers are copied into a fresh snapshot for each run, an
+    /// allocation whatever the storage of the pipeline, use
+    /// [`Pipeline::freeze`] to run the same handlers many times.
+    ///
+    /// The cursor of an outer pipeline already running on the context is
+    /// restored once this run completes.
+    pub fn run<'a>(&self, cx: &'a mut Context) -> BoxFuture<'a, Output>
+    where
+        Context: ContextExt<Output>,
+        Output: Empty + FromNextAlreadyCalled + 'static,
+    {
+        self.hooks.run(self.cursor(), cx)
+    }
+
+    /// Runs the pipeline on the context until the `token` is cancelled.
+    ///
+    /// Once cancelled, [`ContextExt::next`] returns the
+    /// [`Cancelled`](crate::Cancelled) error instead of calling the next
+    /// handler. The handlers already running are not aborted, the code after
+    /// their call to `next` still runs.
+    #[cfg(feature = "std")]
+    pub fn run_until_cancelled<'a>(
+        &self,
+        cx: &'a mut Context,
+        token: &CancelToken,
+    ) -> BoxFuture<'a, Output>
+    where
+        Context: ContextExt<Output>,
+        Output: Empty + FromCancelled + FromNextAlreadyCalled + 'static,
+    {
+        self.hooks.run(self.cursor().with_cancel(token), cx)
+    }
+
+    /// Runs every handler on the context in order and returns all their
+    /// outputs, in the order of the handlers.
+    ///
+    /// Unlike [`Pipeline::run`], the handlers are leaves: each one is called
+    /// with an empty cursor, so [`ContextExt::next`] returns
+    /// [`Empty::empty`] instead of calling the next handler, and a failing
+    /// handler does not stop the following ones. The hooks are not called.
+    pub fn run_all<'a>(&self, cx: &'a mut Context) -> BoxFuture<'a, Vec<Output>>
+    where
+        Context: ContextExt<Output>,
+        Output: Empty + FromNextAlreadyCalled + MaybeSend + 'static,
+    {
+        let handlers: Vec<_> = self.iter().cloned().collect();
+        Box::pin(async move {
+            let prev = mem::take(cx.next_mut());
+            let mut outputs = Vec::with_capacity(handlers.len());
+            for h in &handlers {
+                *cx.next_mut() = Next::default();
+                outputs.push(h.call(&mut *cx).await);
+            }
+            *cx.next_mut() = prev;
+            outputs
+        })
+    }
+
+    fn cursor(&self) -> Next<Context, Output> {
+        Next::new(self.iter().cloned().collect()).with_max_depth(self.max_depth)
+    }
+}
+
+/// Returns the type of the handler behind the pointer.
+fn type_id<Context, Output>(h: &ArcHandle<Context, Output>) -> TypeId {
+    // Not `h.as_any()`, which would be the `Arc` itself.
+    (**h).as_any().type_id()
+}
+
+impl<Context, Output> Default for Pipeline<Context, Output> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<Context, Output> Clone for Pipeline<Context, Output> {
+    fn clone(&self) -> Self {
+        Self {
+            handlers: self.handlers.clone(),
+            max_depth: self.max_depth,
+            hooks: self.hooks.clone(),
+            dedup: self.dedup,
+            order: self.order,
+        }
+    }
+}
+
+impl<Context, Output> fmt::Debug for Pipeline<Context, Output>
+where
+    Context: 'static,
+    Output: 'static,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Pipeline")
+            .field("handlers", &self.names())
+            .finish()
+    }
+}
+
+/// Builds a [`Pipeline`] calling the handlers from left to right.
+///
+/// Closures written as `|cx| async { .. }` are pushed with
+/// [`Pipeline::push_fn`], so they can await the rest of the pipeline through
+/// the context. The output type can be given as a leading `output = Type;`
+/// when it is not inferred.
+///
+/// ```
+/// use futures::executor::block_on;
+/// use handle::{pipeline, ContextExt, Next};
+///
+/// #[derive(Default)]
+/// struct Context {
+///     trace: Vec<&'static str>,
+///     next: Next<Self, ()>,
+/// }
+///
+/// impl ContextExt<()> for Context {
+///     fn next_mut(&mut self) -> &mut Next<Self, ()> {
+///         &mut self.next
+///     }
+///
+///     fn next_ref(&self) -> &Next<Self, ()> {
+///         &self.next
+///     }
+/// }
+///
+/// async fn a(cx: &mut Context) {
+///     cx.trace.push("a");
+///     cx.next().await
+/// }
+///
+/// let pipeline = pipeline![
+///     output = ();
+///     a,
+///     |cx: &mut Context| async {
+///         cx.trace.push("b");
+///         cx.next().await
+///     },
+/// ];
+///
+/// let mut cx = Context::default();
+/// block_on(pipeline.run(&mut cx));
+/// assert_eq!(cx.trace, ["a", "b"]);
+/// ```
+#[macro_export]
+macro_rules! pipeline {
+    (output = $output:ty; $($rest:tt)*) => {{
+        let mut pipeline = $crate::Pipeline::<_, $output>::new();
+        $crate::pipeline!(@push pipeline; $($rest)*);
+        pipeline
+    }};
+    (@push $pipeline:ident;) => {};
+    (@push $pipeline:ident; |$cx:tt $(: $ty:ty)?| $body:expr $(, $($rest:tt)*)?) => {
+        $pipeline.push_fn(move |$cx $(: $ty)?| {
+            $crate::__private::Box::pin(async move { $body.await })
+        });
+        $crate::pipeline!(@push $pipeline; $($($rest)*)?);
+    };
+    (@push $pipeline:ident; $h:expr $(, $($rest:tt)*)?) => {
+        $pipeline.push($h);
+        $crate::pipeline!(@push $pipeline; $($($rest)*)?);
+    };
+    ($($rest:tt)*) => {{
+        let mut pipeline = $crate::Pipeline::new();
+        $crate::pipeline!(@push pipeline; $($rest)*);
+        pipeline
+    }};
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{BoxFuture, ContextExt, Handle, Next, Order, Pipeline};
+    use futures::executor::block_on;
+
+    type Result = anyhow::Result<()>;
+
+    #[derive(Default)]
+    struct Context {
+        trace: Vec<&'static str>,
+        next: Next<Self, Result>,
+    }
+
+    impl ContextExt<Result> for Context {
+        fn next_mut(&mut self) -> &mut Next<Self, Result> {
+            &mut self.next
+        }
+
+        fn next_ref(&self) -> &Next<Self, Result> {
+            &self.next
+        }
+    }
+
+    async fn a(cx: &mut Context) -> Result {
+        cx.trace.push("a>");
+        let output = cx.next().await;
+        cx.trace.push("a<");
+        output
+    }
+
+    async fn b(cx: &mut Context) -> Result {
+        cx.trace.push("b>");
+        let output = cx.next().await;
+        cx.trace.push("b<");
+        output
+    }
+
+    struct C;
+
+    impl<'a> Handle<'a, Context> for C {
+        type Output = Result;
+
+        fn call(&'a self, cx: &'a mut Context) -> BoxFuture<'a, Self::Output> {
+            Box::pin(async move {
+                cx.trace.push("c");
+                cx.next().await
+            })
+        }
+    }
+
+    #[test]
+    fn runs_in_push_order() {
+        let mut pipeline = Pipeline::new();
+        pipeline.push(a).push(b).push(C);
+        assert_eq!(pipeline.len(), 3);
+
+        let mut cx = Context::default();
+        assert!(block_on(pipeline.run(&mut cx)).is_ok());
+        assert_eq!(cx.trace, ["a>", "b>", "c", "b<", "a<"]);
+
+        cx.trace.clear();
+        assert!(block_on(pipeline.run(&mut cx)).is_ok());
+        assert_eq!(cx.trace, ["a>", "b>", "c", "b<", "a<"]);
+    }
+
+    #[test]
+    fn pipeline_fifo() {
+        let mut pipeline = Pipeline::new();
+        pipeline.push(a).push(b).push(C);
+        assert_eq!(pipeline.order(), Order::Fifo);
+
+        for _ in 0..3 {
+            let mut cx = Context::default();
+            assert!(block_on(pipeline.run(&mut cx)).is_ok());
+            assert_eq!(cx.trace, ["a>", "b>", "c", "b<", "a<"]);
+        }
+    }
+
+    #[test]
+    fn pipeline_lifo() {
+        // Pushed like the handlers of a `Vec` popped by the context.
+        let mut pipeline = Pipeline::with_order(Order::Lifo);
+        pipeline.push(C).push(b).push(a);
+        assert_eq!(pipeline.order(), Order::Lifo);
+
+        for _ in 0..3 {
+            let mut cx = Context::default();
+            assert!(block_on(pipeline.run(&mut cx)).is_ok());
+            assert_eq!(cx.trace, ["a>", "b>", "c", "b<", "a<"]);
+        }
+
+        // A higher priority still runs first.
+        pipeline.push_with_priority(C, 1);
+        let mut cx = Context::default();
+        assert!(block_on(pipeline.run(&mut cx)).is_ok());
+        assert_eq!(cx.trace, ["c", "a>", "b>", "c", "b<", "a<"]);
+    }
+
+    struct Group(Pipeline<Context, Result>);
+
+    impl<'a> Handle<'a, Context> for Group {
+        type Output = Result;
+
+        fn call(&'a self, cx: &'a mut Context) -> BoxFuture<'a, Self::Output> {
+            Box::pin(async move {
+                self.0.run(cx).await?;
+                cx.next().await
+            })
+        }
+    }
+
+    #[test]
+    fn macro_runs_left_to_right() {
+        let pipeline = pipeline![
+            a,
+            |cx| async {
+                cx.trace.push("closure");
+                cx.next().await
+            },
+            b,
+            C,
+        ];
+        assert_eq!(pipeline.len(), 4);
+
+        for _ in 0..2 {
+            let mut cx = Context::default();
+            assert!(block_on(pipeline.run(&mut cx)).is_ok());
+            assert_eq!(cx.trace, ["a>", "closure", "b>", "c", "b<", "a<"]);
+        }
+
+        let pipeline = pipeline![output = Result; C];
+        let mut cx = Context::default();
+        assert!(block_on(pipeline.run(&mut cx)).is_ok());
+        assert_eq!(cx.trace, ["c"]);
+    }
+
+    #[test]
+    fn restores_outer_cursor() {
+        let mut inner = Pipeline::new();
+        inner.push(b);
+
+        let mut outer = Pipeline::new();
+        outer.push(Group(inner)).push(C);
+
+        let mut cx = Context::default();
+        assert!(block_on(outer.run(&mut cx)).is_ok());
+        assert_eq!(cx.trace, ["b>", "b<", "c"]);
+    }
+
+    #[test]
+    fn priorities() {
+        let mut forward = Pipeline::new();
+        forward
+            .push_with_priority(a, 3)
+            .push_with_priority(b, 2)
+            .push_with_priority(C, 1);
+
+        let mut reverse = Pipeline::new();
+        reverse
+            .push_with_priority(C, 1)
+            .push_with_priority(b, 2)
+            .push_with_priority(a, 3);
+
+        for pipeline in [forward, reverse] {
+            let mut cx = Context::default();
+            assert!(block_on(pipeline.run(&mut cx)).is_ok());
+            assert_eq!(cx.trace, ["a>", "b>", "c", "b<", "a<"]);
+        }
+
+        // Equal priorities keep the insertion order.
+        let mut pipeline = Pipeline::new();
+        pipeline.push(C).push_first(b).push_first(a).push_last(C);
+        let mut cx = Context::default();
+        assert!(block_on(pipeline.run(&mut cx)).is_ok());
+        assert_eq!(cx.trace, ["a>", "b>", "c", "c", "b<", "a<"]);
+    }
+
+    fn logging(pipeline: &mut Pipeline<Context, Result>) {
+        pipeline.insert_with_priority(i32::MIN, a);
+    }
+
+    fn auth(pipeline: &mut Pipeline<Context, Result>) {
+        pipeline
+            .insert_with_priority(10, C)
+            .insert_with_priority(-5, b);
+    }
+
+    fn routes(pipeline: &mut Pipeline<Context, Result>) {
+        pipeline
+            .insert_with_priority(10, b)
+            .insert_with_priority(i32::MAX, C);
+    }
+
+    #[test]
+    fn insert_with_priority() {
+        let mut pipeline = Pipeline::new();
+        for plugin in [routes, auth, logging] {
+            plugin(&mut pipeline);
+        }
+
+        let stack = pipeline.sorted();
+        assert_eq!(stack.len(), 5);
+
+        let mut cx = Context::default();
+        assert!(block_on(stack.run(&mut cx)).is_ok());
+        // `routes` registered its priority 10 handler before `auth` did.
+        assert_eq!(cx.trace, ["a>", "b>", "b>", "c", "c", "b<", "b<", "a<"]);
+    }
+
+    async fn fail(cx: &mut Context) -> Result {
+        cx.trace.push("fail");
+        Err(anyhow::anyhow!("fail"))
+    }
+
+    #[test]
+    fn run_all_as_leaves() {
+        let mut pipeline = Pipeline::new();
+        pipeline.push(a).push(fail).push(C).push(fail);
+
+        let mut cx = Context::default();
+        let outputs = block_on(pipeline.run_all(&mut cx));
+        let oks: Vec<_> = outputs.iter().map(|o| o.is_ok()).collect();
+        assert_eq!(oks, [true, false, true, false]);
+        // `next` returns at once, so `a` exits before the others are called.
+        assert_eq!(cx.trace, ["a>", "a<", "fail", "c", "fail"]);
+    }
+
+    struct Logger;
+
+    impl<'a> Handle<'a, Context> for Logger {
+        type Output = Result;
+
+        fn call(&'a self, cx: &'a mut Context) -> BoxFuture<'a, Self::Output> {
+            Box::pin(async move {
+                cx.trace.push("log");
+                cx.next().await
+            })
+        }
+    }
+
+    #[test]
+    fn push_unique() {
+        let mut pipeline = Pipeline::new();
+        assert!(pipeline.push_unique(Logger).is_ok());
+        assert!(pipeline.push_unique(Logger).is_err());
+        assert!(pipeline.push_unique(a).is_ok());
+        assert!(pipeline.push_unique(a).is_err());
+        assert!(pipeline.contains::<Logger>());
+
+        let mut cx = Context::default();
+        assert!(block_on(pipeline.run(&mut cx)).is_ok());
+        assert_eq!(cx.trace, ["log", "a>", "a<"]);
+    }
+
+    #[test]
+    fn dedup_by_type() {
+        let mut pipeline = Pipeline::new();
+        pipeline.push(Logger).push(Logger).dedup_by_type();
+        assert_eq!(pipeline.len(), 1);
+
+        pipeline
+            .push(Logger)
+            .push_first(Logger)
+            .push_fn(|cx| {
+                Box::pin(async {
+                    cx.trace.push("x");
+                    cx.next().await
+                })
+            })
+            .push_fn(|cx| {
+                Box::pin(async {
+                    cx.trace.push("y");
+                    cx.next().await
+                })
+            });
+        assert_eq!(pipeline.len(), 3);
+
+        let mut cx = Context::default();
+        assert!(block_on(pipeline.run(&mut cx)).is_ok());
+        assert_eq!(cx.trace, ["log", "x", "y"]);
+    }
+
+    fn steps(names: [&'static str; 3]) -> Pipeline<Context, Result> {
+        let mut pipeline = Pipeline::new();
+        for name in names {
+            pipeline.push_fn(move |cx: &mut Context| {
+                Box::pin(async move {
+                    cx.trace.push(name);
+                    cx.next().await
+                })
+            });
+        }
+        pipeline
+    }
+
+    #[test]
+    fn extend_and_prepend() {
+        let mut pipeline = steps(["a", "b", "c"]);
+        pipeline.extend(steps(["d", "e", "f"]));
+        assert_eq!(pipeline.iter().len(), 6);
+
+        let mut cx = Context::default();
+        assert!(block_on(pipeline.run(&mut cx)).is_ok());
+        assert_eq!(cx.trace, ["a", "b", "c", "d", "e", "f"]);
+
+        let mut pipeline = steps(["a", "b", "c"]);
+        pipeline.prepend(steps(["d", "e", "f"]));
+
+        let mut cx = Context::default();
+        assert!(block_on(pipeline.run(&mut cx)).is_ok());
+        assert_eq!(cx.trace, ["d", "e", "f", "a", "b", "c"]);
+    }
+
+    #[test]
+    fn extend_keeps_priorities() {
+        let mut base = steps(["a", "b", "c"]);
+        base.push_with_priority(C, 10);
+        let mut other = steps(["d", "e", "f"]);
+        other.push_with_priority(b, 10).append(a);
+
+        base.extend(other);
+        let mut cx = Context::default();
+        assert!(block_on(base.run(&mut cx)).is_ok());
+        assert_eq!(
+            cx.trace,
+            ["c", "b>", "a", "b", "c", "d", "e", "f", "a>", "a<", "b<"]
+        );
+    }
+}
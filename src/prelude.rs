@@ -0,0 +1,42 @@
+//! The types and traits needed to write handlers and run pipelines.
+//!
+//! `use handle::prelude::*` brings in the [`Handle`] trait and its adapters,
+//! the [`Pipeline`] with the cursor it keeps in the context, and the pointer
+//! types the handlers are stored as. The adapters and the other wrappers stay
+//! out of it, import them by name when they are used.
+//!
+//! ```
+//! use handle::prelude::*;
+//!
+//! #[derive(Default)]
+//! struct Context {
+//!     hits: usize,
+//!     next: Next<Self, Option<usize>>,
+//! }
+//!
+//! impl ContextExt<Option<usize>> for Context {
+//!     fn next_mut(&mut self) -> &mut Next<Self, Option<usize>> {
+//!         &mut self.next
+//!     }
+//!
+//!     fn next_ref(&self) -> &Next<Self, Option<usize>> {
+//!         &self.next
+//!     }
+//! }
+//!
+//! async fn hit(cx: &mut Context) -> Option<usize> {
+//!     cx.hits += 1;
+//!     cx.next().await.or(Some(cx.hits))
+//! }
+//!
+//! let mut pipeline = Pipeline::new();
+//! pipeline.push(hit).push(hit.named("again"));
+//!
+//! let mut cx = Context::default();
+//! assert_eq!(futures::executor::block_on(pipeline.run(&mut cx)), Some(2));
+//! ```
+
+pub use crate::{
+    ArcHandle, BoxFuture, BoxHandle, ContextExt, DynHandle, Empty, ErasedHandle, Handle, HandleExt,
+    IntoHandle, Next, Pipeline,
+};
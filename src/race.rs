@@ -0,0 +1,127 @@
+use alloc::boxed::Box;
+use core::{future::poll_fn, task::Poll};
+
+use crate::{BoxFuture, Handle, MaybeSend, MaybeSync};
+
+/// Runs two handlers concurrently on owned inputs projected from the context,
+/// and returns the output of the first one to finish.
+///
+/// The `project` is called once per handler, since both can't borrow the
+/// context mutably at the same time. The loser is dropped as soon as the
+/// winner finishes. When both are ready on the same poll, `a` wins.
+#[inline]
+pub const fn race<P, A, B, Context, Input>(project: P, a: A, b: B) -> Race<P, A, B>
+where
+    P: Fn(&Context) -> Input,
+{
+    Race { project, a, b }
+}
+
+/// The side which won a [`race`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Winner<A, B> {
+    /// The first handler finished first.
+    First(A),
+    /// The second handler finished first.
+    Second(B),
+}
+
+/// The handler returned by [`race`].
+#[derive(Debug, Clone)]
+pub struct Race<P, A, B> {
+    project: P,
+    a: A,
+    b: B,
+}
+
+impl<'a, Context, P, A, B, Input, T, U> Handle<'a, Context> for Race<P, A, B>
+where
+    P: Fn(&Context) -> Input + MaybeSend + MaybeSync + 'static,
+    A: for<'b> Handle<'b, Input, Output = T>,
+    B: for<'b> Handle<'b, Input, Output = U>,
+    Input: MaybeSend + 'a,
+    T: 'a,
+    U: 'a,
+{
+    type Output = Winner<T, U>;
+
+    fn call(&'a self, cx: &'a mut Context) -> BoxFuture<'a, Self::Output> {
+        let (mut a, mut b) = ((self.project)(cx), (self.project)(cx));
+
+        Box::pin(async move {
+            let mut a = self.a.call(&mut a);
+            let mut b = self.b.call(&mut b);
+
+            poll_fn(|task| {
+                if let Poll::Ready(o) = a.as_mut().poll(task) {
+                    return Poll::Ready(Winner::First(o));
+                }
+                if let Poll::Ready(o) = b.as_mut().poll(task) {
+                    return Poll::Ready(Winner::Second(o));
+                }
+                Poll::Pending
+            })
+            .await
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{race, Handle, Winner};
+    use async_std::task::sleep;
+    use std::{
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc,
+        },
+        time::Duration,
+    };
+
+    struct Context {
+        key: &'static str,
+        finished: Arc<AtomicUsize>,
+    }
+
+    struct Input {
+        key: &'static str,
+        finished: Arc<AtomicUsize>,
+    }
+
+    fn project(cx: &Context) -> Input {
+        Input {
+            key: cx.key,
+            finished: cx.finished.clone(),
+        }
+    }
+
+    async fn primary(cx: &mut Input) -> String {
+        sleep(Duration::from_millis(500)).await;
+        cx.finished.fetch_add(1, Ordering::Relaxed);
+        format!("primary: {}", cx.key)
+    }
+
+    async fn backup(cx: &mut Input) -> String {
+        sleep(Duration::from_millis(10)).await;
+        cx.finished.fetch_add(1, Ordering::Relaxed);
+        format!("backup: {}", cx.key)
+    }
+
+    #[async_std::test]
+    async fn fast_wins() {
+        let mut cx = Context {
+            key: "user:1",
+            finished: Arc::default(),
+        };
+
+        let output = race(project, primary, backup).call(&mut cx).await;
+        assert_eq!(output, Winner::Second("backup: user:1".to_string()));
+
+        let output = race(project, backup, primary).call(&mut cx).await;
+        assert_eq!(output, Winner::First("backup: user:1".to_string()));
+
+        // The slow handler was dropped before it finished.
+        sleep(Duration::from_millis(600)).await;
+        assert_eq!(cx.finished.load(Ordering::Relaxed), 2);
+    }
+}
@@ -0,0 +1,203 @@
+use alloc::{boxed::Box, sync::Arc, vec::Vec};
+use core::{fmt, future::Future};
+
+use crate::{join::join_all, BoxFuture, Handle, MaybeSend, MaybeSync};
+
+/// A handler which observes the context through a shared reference.
+pub trait ReadHandle<'a, Context>: MaybeSend + MaybeSync + 'static {
+    /// The returned type after the call operator is used.
+    type Output;
+
+    /// Invokes the handler within the given `Context` and then returns `Output`.
+    fn call(&'a self, cx: &'a Context) -> BoxFuture<'a, Self::Output>;
+}
+
+impl<'a, Context, F, Fut> ReadHandle<'a, Context> for F
+where
+    F: Fn(&'a Context) -> Fut + MaybeSend + MaybeSync + 'static,
+    Fut: Future + MaybeSend + 'a,
+    Context: 'a,
+{
+    type Output = Fut::Output;
+
+    fn call(&'a self, cx: &'a Context) -> BoxFuture<'a, Self::Output> {
+        Box::pin((self)(cx))
+    }
+}
+
+/// Lifts a [`ReadHandle`] into a [`Handle`], so it joins a pipeline of
+/// handlers mutating the context.
+#[inline]
+pub const fn read_only<H>(h: H) -> ReadOnly<H> {
+    ReadOnly::new(h)
+}
+
+/// Runs a [`ReadHandle`] as a [`Handle`], reborrowing the context as shared.
+#[derive(Debug, Clone)]
+pub struct ReadOnly<H> {
+    h: H,
+}
+
+impl<H> ReadOnly<H> {
+    /// Creates a new [`ReadOnly`].
+    #[inline]
+    pub const fn new(h: H) -> Self {
+        Self { h }
+    }
+}
+
+impl<'a, Context, H> Handle<'a, Context> for ReadOnly<H>
+where
+    H: ReadHandle<'a, Context>,
+{
+    type Output = H::Output;
+
+    #[inline]
+    fn call(&'a self, cx: &'a mut Context) -> BoxFuture<'a, Self::Output> {
+        self.h.call(cx)
+    }
+}
+
+type ArcReadHandle<Context, Output> = Arc<dyn for<'a> ReadHandle<'a, Context, Output = Output>>;
+
+/// A list of [`ReadHandle`]s running concurrently on the same context.
+pub struct ReadPipeline<Context, Output> {
+    handlers: Vec<ArcReadHandle<Context, Output>>,
+}
+
+impl<Context, Output> ReadPipeline<Context, Output> {
+    /// Creates an empty [`ReadPipeline`].
+    #[inline]
+    pub fn new() -> Self {
+        Self {
+            handlers: Vec::new(),
+        }
+    }
+
+    /// Appends a handler.
+    pub fn push<H>(&mut self, h: H) -> &mut Self
+    where
+        H: for<'a> ReadHandle<'a, Context, Output = Output>,
+    {
+        self.handlers.push(Arc::new(h));
+        self
+    }
+
+    /// Returns the number of handlers in the pipeline.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.handlers.len()
+    }
+
+    /// Returns `true` if the pipeline has no handlers.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.handlers.is_empty()
+    }
+
+    /// Runs all the handlers concurrently on the context, and returns their
+    /// outputs in the order they were pushed.
+    pub fn run<'a>(&'a self, cx: &'a Context) -> BoxFuture<'a, Vec<Output>>
+    where
+        Context: 'static,
+        Output: MaybeSend + 'static,
+    {
+        Box::pin(join_all(self.handlers.iter().map(|h| h.call(cx)).collect()))
+    }
+}
+
+impl<Context, Output> Default for ReadPipeline<Context, Output> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<Context, Output> Clone for ReadPipeline<Context, Output> {
+    fn clone(&self) -> Self {
+        Self {
+            handlers: self.handlers.clone(),
+        }
+    }
+}
+
+impl<Context, Output> fmt::Debug for ReadPipeline<Context, Output> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ReadPipeline")
+            .field("len", &self.handlers.len())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Handle, ReadOnly, ReadPipeline};
+    use async_std::task::sleep;
+    use std::{
+        sync::atomic::{AtomicUsize, Ordering},
+        time::{Duration, Instant},
+    };
+
+    #[derive(Default)]
+    struct Context {
+        path: &'static str,
+        observed: AtomicUsize,
+    }
+
+    async fn log(cx: &Context) -> usize {
+        sleep(Duration::from_millis(100)).await;
+        cx.observed.fetch_add(1, Ordering::Relaxed);
+        cx.path.len()
+    }
+
+    async fn metrics(cx: &Context) -> usize {
+        sleep(Duration::from_millis(100)).await;
+        cx.observed.fetch_add(1, Ordering::Relaxed);
+        1
+    }
+
+    #[async_std::test]
+    async fn concurrent() {
+        let mut pipeline = ReadPipeline::new();
+        pipeline.push(log).push(metrics).push(log);
+
+        let cx = Context {
+            path: "/users",
+            ..Default::default()
+        };
+        let start = Instant::now();
+        assert_eq!(pipeline.run(&cx).await, [6, 1, 6]);
+        assert!(start.elapsed() < Duration::from_millis(250));
+        assert_eq!(cx.observed.load(Ordering::Relaxed), 3);
+    }
+
+    #[async_std::test]
+    async fn read_only() {
+        let mut cx = Context {
+            path: "/",
+            ..Default::default()
+        };
+        assert_eq!(ReadOnly::new(log).call(&mut cx).await, 1);
+    }
+
+    async fn reset(cx: &mut Context) -> usize {
+        *cx.observed.get_mut() = 0;
+        cx.path.len()
+    }
+
+    #[async_std::test]
+    async fn observers_then_mutation() {
+        let mut pipeline = ReadPipeline::new();
+        pipeline.push(log).push(metrics).push(log);
+
+        let mut cx = Context {
+            path: "/",
+            ..Default::default()
+        };
+        assert_eq!(pipeline.run(&cx).await, [1, 1, 1]);
+        assert_eq!(cx.observed.load(Ordering::Relaxed), 3);
+
+        assert_eq!(crate::read_only(metrics).call(&mut cx).await, 1);
+        assert_eq!(Handle::call(&reset, &mut cx).await, 1);
+        assert_eq!(*cx.observed.get_mut(), 0);
+    }
+}
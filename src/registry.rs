@@ -0,0 +1,212 @@
+use std::{
+    fmt,
+    sync::{Arc, PoisonError, RwLock},
+};
+
+use crate::{ArcHandle, Handle, IntoHandle, NamedHandle, Pipeline};
+
+/// The named handlers of a [`HandlerRegistry`], in registration order.
+type Entries<Context, Output> = Vec<(&'static str, ArcHandle<Context, Output>)>;
+
+/// A thread-safe registry of named handlers.
+///
+/// Names are iterated in registration order; registering a name again swaps
+/// the handler in place. Clones share the same registry.
+pub struct HandlerRegistry<Context, Output> {
+    handlers: Arc<RwLock<Entries<Context, Output>>>,
+}
+
+impl<Context, Output> HandlerRegistry<Context, Output> {
+    /// Creates an empty [`HandlerRegistry`].
+    #[inline]
+    pub fn new() -> Self {
+        Self {
+            handlers: Arc::default(),
+        }
+    }
+
+    /// Registers the handler under the `name`, returning the handler it replaces.
+    pub fn register<H, K>(&self, name: &'static str, h: H) -> Option<ArcHandle<Context, Output>>
+    where
+        H: IntoHandle<Context, Output, K>,
+    {
+        self.register_arc(name, h.into_handle())
+    }
+
+    /// Registers a [`NamedHandle`] under its own name.
+    pub fn register_named<H>(&self, h: NamedHandle<H>) -> Option<ArcHandle<Context, Output>>
+    where
+        H: for<'a> Handle<'a, Context, Output = Output>,
+        Context: 'static,
+        Output: 'static,
+    {
+        self.register_arc(h.name(), h.into_handle())
+    }
+
+    fn register_arc(
+        &self,
+        name: &'static str,
+        h: ArcHandle<Context, Output>,
+    ) -> Option<ArcHandle<Context, Output>> {
+        let mut handlers = self
+            .handlers
+            .write()
+            .unwrap_or_else(PoisonError::into_inner);
+        match handlers.iter_mut().find(|(n, _)| *n == name) {
+            Some((_, prev)) => Some(std::mem::replace(prev, h)),
+            None => {
+                handlers.push((name, h));
+                None
+            }
+        }
+    }
+
+    /// Returns the handler registered under the `name`.
+    pub fn get(&self, name: &str) -> Option<ArcHandle<Context, Output>> {
+        self.handlers
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .iter()
+            .find(|(n, _)| *n == name)
+            .map(|(_, h)| h.clone())
+    }
+
+    /// Removes the handler registered under the `name`.
+    pub fn unregister(&self, name: &str) -> Option<ArcHandle<Context, Output>> {
+        let mut handlers = self
+            .handlers
+            .write()
+            .unwrap_or_else(PoisonError::into_inner);
+        let index = handlers.iter().position(|(n, _)| *n == name)?;
+        Some(handlers.remove(index).1)
+    }
+
+    /// Returns the registered names in registration order.
+    pub fn names(&self) -> Vec<&'static str> {
+        self.handlers
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .iter()
+            .map(|(n, _)| *n)
+            .collect()
+    }
+
+    /// Builds a [`Pipeline`] of the registered handlers in registration order.
+    pub fn build_pipeline(&self) -> Pipeline<Context, Output> {
+        let mut pipeline = Pipeline::new();
+        for (_, h) in self
+            .handlers
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .iter()
+        {
+            pipeline.push_arc(h.clone());
+        }
+        pipeline
+    }
+}
+
+impl<Context, Output> Default for HandlerRegistry<Context, Output> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<Context, Output> Clone for HandlerRegistry<Context, Output> {
+    fn clone(&self) -> Self {
+        Self {
+            handlers: self.handlers.clone(),
+        }
+    }
+}
+
+impl<Context, Output> fmt::Debug for HandlerRegistry<Context, Output> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("HandlerRegistry")
+            .field("names", &self.names())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ArcHandle, ContextExt, HandlerRegistry, NamedHandle, Next};
+    use futures::executor::block_on;
+    use std::sync::Arc;
+
+    type Result = anyhow::Result<()>;
+
+    #[derive(Default)]
+    struct Context {
+        trace: Vec<&'static str>,
+        next: Next<Self, Result>,
+    }
+
+    impl ContextExt<Result> for Context {
+        fn next_mut(&mut self) -> &mut Next<Self, Result> {
+            &mut self.next
+        }
+
+        fn next_ref(&self) -> &Next<Self, Result> {
+            &self.next
+        }
+    }
+
+    async fn auth(cx: &mut Context) -> Result {
+        cx.trace.push("auth");
+        cx.next().await
+    }
+
+    async fn mock_auth(cx: &mut Context) -> Result {
+        cx.trace.push("mock_auth");
+        cx.next().await
+    }
+
+    async fn log(cx: &mut Context) -> Result {
+        cx.trace.push("log");
+        cx.next().await
+    }
+
+    async fn index(cx: &mut Context) -> Result {
+        cx.trace.push("index");
+        Ok(())
+    }
+
+    #[test]
+    fn register_and_swap() {
+        let registry = HandlerRegistry::new();
+        assert!(registry.register("log", log).is_none());
+        assert!(registry.register("auth", auth).is_none());
+        assert!(registry
+            .register_named(NamedHandle::new("index", index))
+            .is_none());
+        assert_eq!(registry.names(), ["log", "auth", "index"]);
+
+        let mut cx = Context::default();
+        assert!(block_on(registry.build_pipeline().run(&mut cx)).is_ok());
+        assert_eq!(cx.trace, ["log", "auth", "index"]);
+
+        // Swapping keeps the position, and clones share the registry.
+        let shared = registry.clone();
+        assert!(shared.register("auth", mock_auth).is_some());
+        assert!(registry.unregister("log").is_some());
+        assert!(registry.unregister("log").is_none());
+        assert!(registry.get("auth").is_some());
+        assert!(registry.get("log").is_none());
+        assert_eq!(registry.names(), ["auth", "index"]);
+
+        let mut cx = Context::default();
+        assert!(block_on(registry.build_pipeline().run(&mut cx)).is_ok());
+        assert_eq!(cx.trace, ["mock_auth", "index"]);
+    }
+
+    #[test]
+    fn register_shared() {
+        let registry = HandlerRegistry::new();
+        let h: ArcHandle<Context, Result> = Arc::new(log);
+        registry.register("log", h.clone());
+
+        // The shared handler is registered as is, not wrapped again.
+        assert!(Arc::ptr_eq(&registry.get("log").unwrap(), &h));
+    }
+}
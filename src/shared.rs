@@ -0,0 +1,147 @@
+use std::sync::Arc;
+
+use tokio::sync::{Mutex, MutexGuard};
+
+/// A state shared by the handlers it is given to when they are built.
+///
+/// The handlers hold the clones of the state in their fields, so their
+/// dependencies show in their types instead of hiding in the context. The
+/// clones share the same value behind an asynchronous mutex, which can be held
+/// across `.await`.
+#[derive(Debug, Default)]
+pub struct SharedState<T>(Arc<Mutex<T>>);
+
+impl<T> SharedState<T> {
+    /// Creates a new [`SharedState`].
+    #[inline]
+    pub fn new(value: T) -> Self {
+        Self(Arc::new(Mutex::new(value)))
+    }
+
+    /// Locks the state, waiting for the other holders to release it.
+    #[inline]
+    pub async fn lock(&self) -> MutexGuard<'_, T> {
+        self.0.lock().await
+    }
+
+    /// Locks the state and calls `f` with it, releasing the lock once `f`
+    /// completes.
+    pub async fn with_mut<F, R>(&self, f: F) -> R
+    where
+        F: AsyncFnOnce(&mut T) -> R,
+    {
+        f(&mut *self.lock().await).await
+    }
+
+    /// Returns a clone of the state which only gives `&T` access.
+    #[inline]
+    pub fn read_only(&self) -> ReadOnlyState<T> {
+        ReadOnlyState(self.0.clone())
+    }
+}
+
+impl<T> Clone for SharedState<T> {
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+/// A [`SharedState`] which only gives `&T` access, see
+/// [`SharedState::read_only`].
+#[derive(Debug)]
+pub struct ReadOnlyState<T>(Arc<Mutex<T>>);
+
+impl<T> ReadOnlyState<T> {
+    /// Locks the state and calls `f` with it, releasing the lock once `f`
+    /// completes.
+    pub async fn with<F, R>(&self, f: F) -> R
+    where
+        F: AsyncFnOnce(&T) -> R,
+    {
+        f(&*self.0.lock().await).await
+    }
+}
+
+impl<T> Clone for ReadOnlyState<T> {
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{BoxFuture, ContextExt, Handle, Next, Pipeline, ReadOnlyState, SharedState};
+
+    type Result = std::result::Result<(), &'static str>;
+
+    #[derive(Default)]
+    struct Context {
+        query: &'static str,
+        response: Option<usize>,
+        next: Next<Self, Result>,
+    }
+
+    impl ContextExt<Result> for Context {
+        fn next_mut(&mut self) -> &mut Next<Self, Result> {
+            &mut self.next
+        }
+
+        fn next_ref(&self) -> &Next<Self, Result> {
+            &self.next
+        }
+    }
+
+    #[derive(Default)]
+    struct Pool {
+        queries: Vec<&'static str>,
+    }
+
+    struct QueryHandler(SharedState<Pool>);
+
+    impl<'a> Handle<'a, Context> for QueryHandler {
+        type Output = Result;
+
+        fn call(&'a self, cx: &'a mut Context) -> BoxFuture<'a, Self::Output> {
+            Box::pin(async move {
+                let query = cx.query;
+                self.0
+                    .with_mut(async |pool: &mut Pool| pool.queries.push(query))
+                    .await;
+                cx.next().await
+            })
+        }
+    }
+
+    struct ResponseHandler(ReadOnlyState<Pool>);
+
+    impl<'a> Handle<'a, Context> for ResponseHandler {
+        type Output = Result;
+
+        fn call(&'a self, cx: &'a mut Context) -> BoxFuture<'a, Self::Output> {
+            Box::pin(async move {
+                let count = self.0.with(async |pool: &Pool| pool.queries.len()).await;
+                cx.response = Some(count);
+                cx.next().await
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn shared_by_handlers() {
+        let pool = SharedState::new(Pool::default());
+        let mut pipeline = Pipeline::new();
+        pipeline
+            .push(QueryHandler(pool.clone()))
+            .push(ResponseHandler(pool.read_only()));
+
+        for (i, query) in ["select 1", "select 2"].into_iter().enumerate() {
+            let mut cx = Context {
+                query,
+                ..Default::default()
+            };
+            assert_eq!(pipeline.run(&mut cx).await, Ok(()));
+            assert_eq!(cx.response, Some(i + 1));
+        }
+        assert_eq!(pool.lock().await.queries, ["select 1", "select 2"]);
+    }
+}
@@ -0,0 +1,112 @@
+use crate::{BoxFuture, Handle, MaybeSend};
+use alloc::boxed::Box;
+
+/// Restores the context when the handler fails.
+///
+/// The context is cloned before calling the handler. When the handler returns
+/// an error, panics or is dropped before it completes, the context is restored
+/// to that clone. On success, the mutations made by the handler stand.
+#[derive(Debug, Clone)]
+pub struct Snapshot<H> {
+    h: H,
+}
+
+impl<H> Snapshot<H> {
+    /// Creates a new [`Snapshot`].
+    #[inline]
+    pub const fn new(h: H) -> Self {
+        Self { h }
+    }
+}
+
+/// Restores the context on drop unless disarmed.
+struct Guard<'a, Context: Clone> {
+    cx: &'a mut Context,
+    snapshot: Option<Context>,
+}
+
+impl<Context: Clone> Drop for Guard<'_, Context> {
+    fn drop(&mut self) {
+        if let Some(snapshot) = self.snapshot.take() {
+            *self.cx = snapshot;
+        }
+    }
+}
+
+impl<'a, Context, H, T, E> Handle<'a, Context> for Snapshot<H>
+where
+    H: for<'b> Handle<'b, Context, Output = Result<T, E>>,
+    Context: Clone + MaybeSend + 'a,
+    T: MaybeSend + 'a,
+    E: MaybeSend + 'a,
+{
+    type Output = Result<T, E>;
+
+    fn call(&'a self, cx: &'a mut Context) -> BoxFuture<'a, Self::Output> {
+        Box::pin(async move {
+            let snapshot = Some(cx.clone());
+            let mut guard = Guard { cx, snapshot };
+            let output = self.h.call(&mut *guard.cx).await;
+            if output.is_ok() {
+                guard.snapshot = None;
+            }
+            output
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Handle, HandleExt};
+    use anyhow::{anyhow, Result};
+    use futures::executor::block_on;
+    use std::panic::{catch_unwind, AssertUnwindSafe};
+
+    #[derive(Clone, Default)]
+    struct Context {
+        index: usize,
+        fail: bool,
+    }
+
+    async fn write(cx: &mut Context) -> Result<()> {
+        cx.index += 1;
+        if cx.fail {
+            Err(anyhow!("boom"))
+        } else {
+            Ok(())
+        }
+    }
+
+    async fn panic(cx: &mut Context) -> Result<()> {
+        cx.index += 1;
+        panic!("boom")
+    }
+
+    #[test]
+    fn commit_on_ok() {
+        let mut cx = Context::default();
+        assert!(block_on(write.snapshot().call(&mut cx)).is_ok());
+        assert_eq!(cx.index, 1);
+    }
+
+    #[test]
+    fn restore_on_err() {
+        let mut cx = Context {
+            index: 3,
+            fail: true,
+        };
+        assert!(block_on(write.snapshot().call(&mut cx)).is_err());
+        assert_eq!(cx.index, 3);
+    }
+
+    #[test]
+    fn restore_on_panic() {
+        let mut cx = Context {
+            index: 3,
+            ..Default::default()
+        };
+        let h = panic.snapshot();
+        assert!(catch_unwind(AssertUnwindSafe(|| block_on(h.call(&mut cx)))).is_err());
+        assert_eq!(cx.index, 3);
+    }
+}
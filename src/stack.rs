@@ -0,0 +1,145 @@
+use alloc::{sync::Arc, vec::Vec};
+use core::fmt;
+
+use crate::{
+    depth::MaxDepth, hooks::Hooks, ArcHandle, BoxFuture, ContextExt, Empty, FromNextAlreadyCalled,
+    Next, Pipeline,
+};
+
+/// A frozen snapshot of a [`Pipeline`], cheap to run many times.
+///
+/// The handlers are shared behind an `Arc<[_]>`, so each run only clones that
+/// `Arc` into the context's [`Next`] cursor instead of copying the handlers.
+pub struct Stack<Context, Output> {
+    handlers: Arc<[ArcHandle<Context, Output>]>,
+    max_depth: Option<MaxDepth<Output>>,
+    hooks: Arc<Hooks<Context, Output>>,
+}
+
+impl<Context, Output> Stack<Context, Output> {
+    /// Returns the number of handlers in the stack.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.handlers.len()
+    }
+
+    /// Returns `true` if the stack has no handlers.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.handlers.is_empty()
+    }
+
+    /// Returns the names of the handlers in order.
+    pub fn names(&self) -> Vec<&str>
+    where
+        Context: 'static,
+        Output: 'static,
+    {
+        self.handlers.iter().map(|h| h.name()).collect()
+    }
+
+    /// Runs the stack on the context.
+    ///
+    /// The cursor of an outer pipeline already running on the context is
+    /// restored once this run completes.
+    pub fn run<'a>(&self, cx: &'a mut Context) -> BoxFuture<'a, Output>
+    where
+        Context: ContextExt<Output>,
+        Output: Empty + FromNextAlreadyCalled + 'static,
+    {
+        let next = Next::new(self.handlers.clone()).with_max_depth(self.max_depth);
+        self.hooks.run(next, cx)
+    }
+}
+
+impl<Context, Output> From<&Pipeline<Context, Output>> for Stack<Context, Output> {
+    fn from(pipeline: &Pipeline<Context, Output>) -> Self {
+        Self {
+            handlers: pipeline.iter().cloned().collect(),
+            max_depth: pipeline.get_max_depth(),
+            hooks: pipeline.get_hooks().clone(),
+        }
+    }
+}
+
+impl<Context, Output> From<Pipeline<Context, Output>> for Stack<Context, Output> {
+    #[inline]
+    fn from(pipeline: Pipeline<Context, Output>) -> Self {
+        Self::from(&pipeline)
+    }
+}
+
+impl<Context, Output> Clone for Stack<Context, Output> {
+    fn clone(&self) -> Self {
+        Self {
+            handlers: self.handlers.clone(),
+            max_depth: self.max_depth,
+            hooks: self.hooks.clone(),
+        }
+    }
+}
+
+impl<Context, Output> fmt::Debug for Stack<Context, Output>
+where
+    Context: 'static,
+    Output: 'static,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Stack")
+            .field("handlers", &self.names())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ContextExt, Next, Pipeline};
+    use futures::executor::block_on;
+    use std::sync::Arc;
+
+    type Result = anyhow::Result<()>;
+
+    #[derive(Default)]
+    struct Context {
+        trace: Vec<&'static str>,
+        next: Next<Self, Result>,
+    }
+
+    impl ContextExt<Result> for Context {
+        fn next_mut(&mut self) -> &mut Next<Self, Result> {
+            &mut self.next
+        }
+
+        fn next_ref(&self) -> &Next<Self, Result> {
+            &self.next
+        }
+    }
+
+    async fn a(cx: &mut Context) -> Result {
+        cx.trace.push("a");
+        cx.next().await
+    }
+
+    async fn b(cx: &mut Context) -> Result {
+        cx.trace.push("b");
+        cx.next().await
+    }
+
+    #[test]
+    fn reuse_snapshot() {
+        let mut pipeline = Pipeline::new();
+        pipeline.push(a).push(b);
+        let stack = pipeline.freeze();
+        let handlers = stack.handlers.clone();
+
+        for _ in 0..3 {
+            let mut cx = Context::default();
+            assert!(block_on(stack.run(&mut cx)).is_ok());
+            assert_eq!(cx.trace, ["a", "b"]);
+        }
+
+        // Runs share the snapshot, the handlers themselves are never cloned.
+        assert_eq!(Arc::strong_count(&handlers), 2);
+        assert!(handlers.iter().all(|h| Arc::strong_count(h) == 2));
+    }
+}
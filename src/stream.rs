@@ -0,0 +1,230 @@
+use std::{fmt, future::poll_fn, pin::Pin, sync::Arc};
+
+use futures_core::Stream;
+
+use crate::{BoxFuture, Handle, MaybeSend, MaybeSync};
+
+/// A boxed [`Stream`] trait object.
+#[cfg(feature = "send")]
+pub type BoxStream<'a, T> = Pin<Box<dyn Stream<Item = T> + Send + 'a>>;
+
+/// A boxed [`Stream`] trait object.
+#[cfg(not(feature = "send"))]
+pub type BoxStream<'a, T> = Pin<Box<dyn Stream<Item = T> + 'a>>;
+
+/// A handler which produces a stream of items instead of a single output.
+pub trait HandleStream<'a, Context>: MaybeSend + MaybeSync + 'static {
+    /// The items of the stream.
+    type Item;
+
+    /// Invokes the handler within the given `Context` and then returns a
+    /// stream of `Item`s.
+    fn call(&'a self, cx: &'a mut Context) -> BoxStream<'a, Self::Item>;
+
+    /// Collects the stream into a `Vec`, so the handler can end an ordinary
+    /// [`Pipeline`](crate::Pipeline).
+    fn into_collect(self) -> Collect<Self>
+    where
+        Self: Sized,
+    {
+        Collect::new(self)
+    }
+}
+
+impl<'a, Context, F, S> HandleStream<'a, Context> for F
+where
+    F: Fn(&'a mut Context) -> S + MaybeSend + MaybeSync + 'static,
+    S: Stream + MaybeSend + 'a,
+    Context: 'a,
+{
+    type Item = S::Item;
+
+    fn call(&'a self, cx: &'a mut Context) -> BoxStream<'a, Self::Item> {
+        Box::pin((self)(cx))
+    }
+}
+
+/// A [`Handle`] collecting the stream of a [`HandleStream`] into a `Vec`.
+#[derive(Debug, Clone)]
+pub struct Collect<H> {
+    h: H,
+}
+
+impl<H> Collect<H> {
+    /// Creates a new [`Collect`].
+    #[inline]
+    pub const fn new(h: H) -> Self {
+        Self { h }
+    }
+}
+
+impl<'a, Context, H, Item> Handle<'a, Context> for Collect<H>
+where
+    H: HandleStream<'a, Context, Item = Item>,
+    Item: MaybeSend + 'static,
+{
+    type Output = Vec<Item>;
+
+    fn call(&'a self, cx: &'a mut Context) -> BoxFuture<'a, Self::Output> {
+        let mut stream = self.h.call(cx);
+
+        Box::pin(async move {
+            let mut items = Vec::new();
+            while let Some(item) = poll_fn(|cx| stream.as_mut().poll_next(cx)).await {
+                items.push(item);
+            }
+            items
+        })
+    }
+}
+
+type ArcHandleStream<Context, Item> = Arc<dyn for<'a> HandleStream<'a, Context, Item = Item>>;
+
+/// An ordered list of streaming handlers whose streams are chained.
+///
+/// Each handler is called once the stream of the previous one has ended, so it
+/// sees the context mutations made while that stream was polled.
+pub struct StreamPipeline<Context, Item> {
+    handlers: Vec<ArcHandleStream<Context, Item>>,
+}
+
+impl<Context, Item> StreamPipeline<Context, Item> {
+    /// Creates an empty [`StreamPipeline`].
+    #[inline]
+    pub fn new() -> Self {
+        Self {
+            handlers: Vec::new(),
+        }
+    }
+
+    /// Appends a handler.
+    pub fn push<H>(&mut self, h: H) -> &mut Self
+    where
+        H: for<'a> HandleStream<'a, Context, Item = Item>,
+    {
+        self.handlers.push(Arc::new(h));
+        self
+    }
+
+    /// Returns the number of handlers in the pipeline.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.handlers.len()
+    }
+
+    /// Returns `true` if the pipeline has no handlers.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.handlers.is_empty()
+    }
+
+    /// Runs the pipeline on the context, chaining the streams of the handlers.
+    pub fn run<'a>(&self, cx: &'a mut Context) -> BoxStream<'a, Item>
+    where
+        Context: MaybeSend + 'static,
+        Item: MaybeSend + 'static,
+    {
+        let handlers = self.handlers.clone();
+
+        Box::pin(async_stream::stream! {
+            for h in handlers {
+                let mut stream = h.call(&mut *cx);
+                while let Some(item) = poll_fn(|cx| stream.as_mut().poll_next(cx)).await {
+                    yield item;
+                }
+            }
+        })
+    }
+}
+
+impl<Context, Item> Default for StreamPipeline<Context, Item> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<Context, Item> Clone for StreamPipeline<Context, Item> {
+    fn clone(&self) -> Self {
+        Self {
+            handlers: self.handlers.clone(),
+        }
+    }
+}
+
+impl<Context, Item> fmt::Debug for StreamPipeline<Context, Item> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("StreamPipeline")
+            .field("len", &self.handlers.len())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ContextExt, HandleStream, MaybeSend, Next, Pipeline, StreamPipeline};
+    use futures::{executor::block_on, stream, Stream, StreamExt};
+
+    #[derive(Default)]
+    struct Context {
+        sent: usize,
+        next: Next<Self, Vec<String>>,
+    }
+
+    impl ContextExt<Vec<String>> for Context {
+        fn next_mut(&mut self) -> &mut Next<Self, Vec<String>> {
+            &mut self.next
+        }
+
+        fn next_ref(&self) -> &Next<Self, Vec<String>> {
+            &self.next
+        }
+    }
+
+    async fn auth(cx: &mut Context) -> Vec<String> {
+        let mut items = cx.next().await;
+        items.insert(0, "auth".to_string());
+        items
+    }
+
+    fn events(cx: &mut Context) -> impl Stream<Item = String> + MaybeSend + '_ {
+        stream::iter(["open", "message"]).map(move |event| {
+            cx.sent += 1;
+            event.to_string()
+        })
+    }
+
+    fn summary(cx: &mut Context) -> impl Stream<Item = String> + MaybeSend + '_ {
+        stream::once(async move { format!("sent {}", cx.sent) })
+    }
+
+    #[test]
+    fn single() {
+        let mut cx = Context::default();
+        let items: Vec<_> = block_on(HandleStream::call(&events, &mut cx).collect());
+        assert_eq!(items, ["open", "message"]);
+        assert_eq!(cx.sent, 2);
+    }
+
+    #[test]
+    fn chain() {
+        let mut pipeline = StreamPipeline::new();
+        pipeline.push(events).push(summary).push(events);
+        assert_eq!(pipeline.len(), 3);
+
+        let mut cx = Context::default();
+        let items: Vec<_> = block_on(pipeline.run(&mut cx).collect());
+        assert_eq!(items, ["open", "message", "sent 2", "open", "message"]);
+        assert_eq!(cx.sent, 4);
+    }
+
+    #[test]
+    fn into_collect() {
+        let mut pipeline = Pipeline::new();
+        pipeline.push(auth).push(events.into_collect());
+
+        let mut cx = Context::default();
+        let items = block_on(pipeline.run(&mut cx));
+        assert_eq!(items, ["auth", "open", "message"]);
+        assert_eq!(cx.sent, 2);
+    }
+}
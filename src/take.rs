@@ -0,0 +1,143 @@
+use alloc::{boxed::Box, sync::Arc};
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::{BoxFuture, ContextExt, Empty, FromNextAlreadyCalled, Handle, MaybeSend};
+
+/// Calls the handler for the first `n` calls only, see
+/// [`HandleExt::take`](crate::HandleExt::take).
+///
+/// Once the budget is spent, the handler is skipped and the next handler of
+/// the pipeline is called instead, so a standalone call returns
+/// [`Empty::empty`]. The clones share the count of calls.
+#[derive(Debug, Clone)]
+pub struct Take<H> {
+    h: H,
+    n: usize,
+    calls: Arc<AtomicUsize>,
+}
+
+impl<H> Take<H> {
+    /// Creates a new [`Take`].
+    #[inline]
+    pub fn new(h: H, n: usize) -> Self {
+        Self {
+            h,
+            n,
+            calls: Arc::new(AtomicUsize::new(0)),
+        }
+    }
+
+    /// Returns the number of calls left before the handler is skipped.
+    #[inline]
+    pub fn remaining(&self) -> usize {
+        self.n.saturating_sub(self.calls.load(Ordering::Acquire))
+    }
+
+    /// Takes a call from the budget, returning `false` once it is spent.
+    fn acquire(&self) -> bool {
+        self.calls
+            .fetch_update(Ordering::AcqRel, Ordering::Acquire, |calls| {
+                (calls < self.n).then_some(calls + 1)
+            })
+            .is_ok()
+    }
+}
+
+impl<'a, Context, H> Handle<'a, Context> for Take<H>
+where
+    H: Handle<'a, Context>,
+    H::Output: Empty + FromNextAlreadyCalled + 'static,
+    Context: ContextExt<H::Output> + MaybeSend,
+{
+    type Output = H::Output;
+
+    fn call(&'a self, cx: &'a mut Context) -> BoxFuture<'a, Self::Output> {
+        if self.acquire() {
+            self.h.call(cx)
+        } else {
+            Box::pin(async move { cx.next().await })
+        }
+    }
+
+    #[inline]
+    fn name(&self) -> &str {
+        self.h.name()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ContextExt, Handle, HandleExt, Next, Pipeline};
+    use futures::executor::block_on;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    type Result = anyhow::Result<()>;
+
+    #[derive(Default)]
+    struct Context {
+        trace: Vec<&'static str>,
+        next: Next<Self, Result>,
+    }
+
+    impl ContextExt<Result> for Context {
+        fn next_mut(&mut self) -> &mut Next<Self, Result> {
+            &mut self.next
+        }
+
+        fn next_ref(&self) -> &Next<Self, Result> {
+            &self.next
+        }
+    }
+
+    static WARMED: AtomicUsize = AtomicUsize::new(0);
+
+    async fn warm_up(cx: &mut Context) -> Result {
+        WARMED.fetch_add(1, Ordering::SeqCst);
+        cx.trace.push("warm_up");
+        cx.next().await
+    }
+
+    async fn route(cx: &mut Context) -> Result {
+        cx.trace.push("route");
+        Ok(())
+    }
+
+    #[test]
+    fn take() {
+        let warm_up = warm_up.take(2);
+        let mut pipeline = Pipeline::new();
+        pipeline.push(warm_up.clone()).push(route);
+
+        let traces: Vec<_> = (0..5)
+            .map(|_| {
+                let mut cx = Context::default();
+                block_on(pipeline.run(&mut cx)).unwrap();
+                cx.trace
+            })
+            .collect();
+
+        assert_eq!(WARMED.load(Ordering::SeqCst), 2);
+        assert_eq!(traces[..2], [["warm_up", "route"], ["warm_up", "route"]]);
+        assert!(traces[2..].iter().all(|trace| *trace == ["route"]));
+        // The clone in the pipeline spent the budget of the original.
+        assert_eq!(warm_up.remaining(), 0);
+    }
+
+    #[test]
+    fn once_standalone() {
+        let h = (|cx: &mut Context| {
+            cx.trace.push("once");
+            async { Ok(()) }
+        })
+        .once();
+
+        let traces: Vec<_> = (0..3)
+            .map(|_| {
+                let mut cx = Context::default();
+                block_on(h.call(&mut cx)).unwrap();
+                cx.trace
+            })
+            .collect();
+        assert_eq!(traces, [vec!["once"], vec![], vec![]]);
+    }
+}
@@ -0,0 +1,385 @@
+//! Utilities for testing handlers and pipelines.
+
+use std::{
+    collections::VecDeque,
+    fmt,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex, PoisonError,
+    },
+    time::Instant,
+};
+
+use crate::{BoxFuture, Handle, MaybeSend};
+
+/// Orders the calls of all the mocks, so the calls of different mocks can be
+/// compared.
+static ORDER: AtomicU64 = AtomicU64::new(0);
+
+/// A call recorded by a [`MockHandle`].
+#[derive(Debug, Clone)]
+pub struct Call<Snapshot> {
+    /// When the call started.
+    pub at: Instant,
+    /// The position of the call among the calls of all the mocks.
+    pub order: u64,
+    /// The snapshot taken from the context, if the mock takes one.
+    pub snapshot: Option<Snapshot>,
+}
+
+type SnapshotFn<Context, Snapshot> = Arc<dyn Fn(&Context) -> Snapshot + Send + Sync>;
+
+/// A handler returning canned outputs and recording its calls.
+///
+/// The clones share the outputs and the calls, so the same mock can be placed
+/// at several positions of a pipeline.
+pub struct MockHandle<Context, Output, Snapshot = ()> {
+    outputs: Arc<Mutex<VecDeque<Output>>>,
+    snapshot: Option<SnapshotFn<Context, Snapshot>>,
+    calls: Arc<Mutex<Vec<Call<Snapshot>>>>,
+}
+
+impl<Context, Output> MockHandle<Context, Output> {
+    /// Creates a new [`MockHandle`] returning `output` for every call.
+    #[inline]
+    pub fn returning(output: Output) -> Self {
+        Self::sequence([output])
+    }
+
+    /// Creates a new [`MockHandle`] returning the `outputs` in order, the last
+    /// one is returned for all the following calls.
+    ///
+    /// # Panics
+    ///
+    /// Panics when `outputs` is empty.
+    pub fn sequence(outputs: impl IntoIterator<Item = Output>) -> Self {
+        let outputs: VecDeque<_> = outputs.into_iter().collect();
+        assert!(
+            !outputs.is_empty(),
+            "`MockHandle` needs at least one output"
+        );
+        Self {
+            outputs: Arc::new(Mutex::new(outputs)),
+            snapshot: None,
+            calls: Arc::default(),
+        }
+    }
+
+    /// Takes a snapshot of the context with `f` on each call.
+    ///
+    /// The calls recorded so far are dropped, so call it before cloning the
+    /// mock.
+    pub fn with_snapshot<Snapshot, F>(self, f: F) -> MockHandle<Context, Output, Snapshot>
+    where
+        F: Fn(&Context) -> Snapshot + Send + Sync + 'static,
+    {
+        MockHandle {
+            outputs: self.outputs,
+            snapshot: Some(Arc::new(f)),
+            calls: Arc::default(),
+        }
+    }
+}
+
+impl<Context, Output, Snapshot> MockHandle<Context, Output, Snapshot> {
+    /// Returns the recorded calls in order.
+    pub fn calls(&self) -> Vec<Call<Snapshot>>
+    where
+        Snapshot: Clone,
+    {
+        self.calls
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+
+    /// Returns the number of calls.
+    pub fn times_called(&self) -> usize {
+        self.calls
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .len()
+    }
+
+    /// Asserts that the mock was called exactly once.
+    ///
+    /// # Panics
+    ///
+    /// Panics when the mock was called another number of times.
+    #[track_caller]
+    pub fn assert_called_once(&self) {
+        let n = self.times_called();
+        assert_eq!(
+            n, 1,
+            "expected `MockHandle` to be called once, got {n} calls"
+        );
+    }
+
+    fn next_output(&self) -> Output
+    where
+        Output: Clone,
+    {
+        let mut outputs = self.outputs.lock().unwrap_or_else(PoisonError::into_inner);
+        if outputs.len() > 1 {
+            outputs
+                .pop_front()
+                .expect("`MockHandle` outputs are never empty")
+        } else {
+            outputs[0].clone()
+        }
+    }
+}
+
+impl<'a, Context, Output, Snapshot> Handle<'a, Context> for MockHandle<Context, Output, Snapshot>
+where
+    Context: 'static,
+    Output: Clone + MaybeSend + 'static,
+    Snapshot: MaybeSend + 'static,
+{
+    type Output = Output;
+
+    fn call(&'a self, cx: &'a mut Context) -> BoxFuture<'a, Self::Output> {
+        let call = Call {
+            at: Instant::now(),
+            order: ORDER.fetch_add(1, Ordering::Relaxed),
+            snapshot: self.snapshot.as_ref().map(|f| f(cx)),
+        };
+        self.calls
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .push(call);
+
+        let output = self.next_output();
+        Box::pin(async move { output })
+    }
+}
+
+impl<Context, Output, Snapshot> Clone for MockHandle<Context, Output, Snapshot> {
+    fn clone(&self) -> Self {
+        Self {
+            outputs: self.outputs.clone(),
+            snapshot: self.snapshot.clone(),
+            calls: self.calls.clone(),
+        }
+    }
+}
+
+impl<Context, Output, Snapshot> fmt::Debug for MockHandle<Context, Output, Snapshot> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MockHandle")
+            .field("times_called", &self.times_called())
+            .finish()
+    }
+}
+
+/// An event recorded by a [`Recorder`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Event {
+    /// The named handler was called.
+    Enter(&'static str),
+    /// The named handler returned, `true` when it returned `Ok`.
+    Exit(&'static str, bool),
+}
+
+impl fmt::Display for Event {
+    /// Formats the event as `name>` when entering, and `name<` or `name!` when
+    /// exiting with `Ok` or `Err`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Enter(name) => write!(f, "{name}>"),
+            Self::Exit(name, true) => write!(f, "{name}<"),
+            Self::Exit(name, false) => write!(f, "{name}!"),
+        }
+    }
+}
+
+/// Records when the handlers of a pipeline are entered and exited.
+///
+/// The clones share the events, so a single recorder can wrap all the
+/// handlers of a pipeline with [`Recorder::recorded`].
+#[derive(Debug, Clone, Default)]
+pub struct Recorder(Arc<Mutex<Vec<Event>>>);
+
+impl Recorder {
+    /// Creates a new [`Recorder`].
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Wraps the handler `h` to record its events under `name`.
+    #[inline]
+    pub fn recorded<H>(&self, name: &'static str, h: H) -> Recorded<H> {
+        Recorded {
+            name,
+            h,
+            recorder: self.clone(),
+        }
+    }
+
+    /// Returns the recorded events in order.
+    pub fn events(&self) -> Vec<Event> {
+        self.0
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+
+    /// Drops the recorded events.
+    pub fn clear(&self) {
+        self.0
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clear();
+    }
+
+    /// Asserts that the recorded events are `expected`, in the format of
+    /// [`Event`]'s `Display`.
+    ///
+    /// # Panics
+    ///
+    /// Panics when the events differ.
+    #[track_caller]
+    pub fn assert_sequence<'s>(&self, expected: impl IntoIterator<Item = &'s str>) {
+        let events: Vec<_> = self.events().iter().map(Event::to_string).collect();
+        let expected: Vec<_> = expected.into_iter().collect();
+        assert_eq!(events, expected, "unexpected sequence of handler events");
+    }
+
+    fn push(&self, event: Event) {
+        self.0
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .push(event);
+    }
+}
+
+/// The handler returned by [`Recorder::recorded`].
+#[derive(Debug, Clone)]
+pub struct Recorded<H> {
+    name: &'static str,
+    h: H,
+    recorder: Recorder,
+}
+
+impl<'a, Context, H, T, E> Handle<'a, Context> for Recorded<H>
+where
+    H: Handle<'a, Context, Output = Result<T, E>>,
+    T: MaybeSend + 'a,
+    E: MaybeSend + 'a,
+{
+    type Output = Result<T, E>;
+
+    fn call(&'a self, cx: &'a mut Context) -> BoxFuture<'a, Self::Output> {
+        self.recorder.push(Event::Enter(self.name));
+        let fut = self.h.call(cx);
+
+        Box::pin(async move {
+            let output = fut.await;
+            self.recorder.push(Event::Exit(self.name, output.is_ok()));
+            output
+        })
+    }
+
+    #[inline]
+    fn name(&self) -> &str {
+        self.name
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{MockHandle, Recorder};
+    use crate::{hpipeline, ContextExt, Handle, Next, Pipeline};
+    use futures::executor::block_on;
+
+    type Result = std::result::Result<(), &'static str>;
+
+    #[derive(Default)]
+    struct Context {
+        index: usize,
+        next: Next<Self, Result>,
+    }
+
+    impl ContextExt<Result> for Context {
+        fn next_mut(&mut self) -> &mut Next<Self, Result> {
+            &mut self.next
+        }
+
+        fn next_ref(&self) -> &Next<Self, Result> {
+            &self.next
+        }
+    }
+
+    async fn forward(cx: &mut Context) -> Result {
+        cx.next().await
+    }
+
+    async fn fail(_: &mut Context) -> Result {
+        Err("fail")
+    }
+
+    #[test]
+    fn records_order() {
+        let auth = MockHandle::<Context, Result>::returning(Ok(()));
+        let log = MockHandle::<Context, Result>::returning(Ok(()));
+        let h = hpipeline!(log.clone(), auth.clone(), log.clone());
+
+        assert_eq!(block_on(h.call(&mut Context::default())), Ok(()));
+
+        auth.assert_called_once();
+        assert_eq!(log.times_called(), 2);
+        let (log, auth) = (log.calls(), auth.calls());
+        assert!(log[0].order < auth[0].order);
+        assert!(auth[0].order < log[1].order);
+        assert!(log[0].at <= log[1].at);
+    }
+
+    #[test]
+    fn sequence_and_snapshot() {
+        let mock =
+            MockHandle::sequence([Ok(()), Err("busy")]).with_snapshot(|cx: &Context| cx.index);
+
+        let mut cx = Context::default();
+        let mut outputs = Vec::new();
+        for index in 1..=3 {
+            cx.index = index;
+            outputs.push(block_on(mock.call(&mut cx)));
+        }
+
+        assert_eq!(outputs, [Ok(()), Err("busy"), Err("busy")]);
+        let snapshots: Vec<_> = mock.calls().into_iter().map(|c| c.snapshot).collect();
+        assert_eq!(snapshots, [Some(1), Some(2), Some(3)]);
+    }
+
+    #[test]
+    #[should_panic(expected = "expected `MockHandle` to be called once, got 0 calls")]
+    fn not_called() {
+        MockHandle::<Context, Result>::returning(Ok(())).assert_called_once();
+    }
+
+    #[test]
+    fn nested_sequence() {
+        let recorder = Recorder::new();
+        let mut pipeline = Pipeline::new();
+        pipeline
+            .push(recorder.recorded("a", forward))
+            .push(recorder.recorded("b", forward));
+
+        assert_eq!(block_on(pipeline.run(&mut Context::default())), Ok(()));
+        recorder.assert_sequence(["a>", "b>", "b<", "a<"]);
+    }
+
+    #[test]
+    fn error_sequence() {
+        let recorder = Recorder::new();
+        let mut pipeline = Pipeline::new();
+        pipeline
+            .push(recorder.recorded("a", forward))
+            .push(recorder.recorded("b", fail))
+            .push(recorder.recorded("c", forward));
+
+        assert_eq!(block_on(pipeline.run(&mut Context::default())), Err("fail"));
+        recorder.assert_sequence(["a>", "b>", "b!", "a!"]);
+    }
+}
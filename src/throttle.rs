@@ -0,0 +1,117 @@
+use std::sync::Arc;
+
+use tokio::sync::Semaphore;
+
+use crate::{BoxFuture, Handle, MaybeSend};
+
+/// Limits how many calls of the handler run at the same time.
+///
+/// A call waits for a permit of the semaphore before calling the handler and
+/// holds it until the future of the handler completes or is dropped. The
+/// clones share the semaphore, so the limit holds across all of them.
+///
+/// The permits are handed out fairly: the waiting calls are resumed in the
+/// order they started to wait, and a new call never overtakes them even when
+/// a permit is free at that instant. There is no unfair mode, which would
+/// let a burst of new calls starve the ones already waiting.
+#[derive(Debug, Clone)]
+pub struct Throttle<H> {
+    h: H,
+    semaphore: Arc<Semaphore>,
+}
+
+impl<H> Throttle<H> {
+    /// Creates a new [`Throttle`] running at most `max_concurrent` calls at
+    /// the same time.
+    ///
+    /// # Panics
+    ///
+    /// Panics when `max_concurrent` is zero, since no call could ever run, or
+    /// exceeds [`Semaphore::MAX_PERMITS`].
+    pub fn new(h: H, max_concurrent: usize) -> Self {
+        assert!(max_concurrent > 0, "`Throttle` needs at least one permit");
+        Self {
+            h,
+            semaphore: Arc::new(Semaphore::new(max_concurrent)),
+        }
+    }
+
+    /// Returns the number of calls which could start right now.
+    #[inline]
+    pub fn available(&self) -> usize {
+        self.semaphore.available_permits()
+    }
+}
+
+impl<'a, Context, H> Handle<'a, Context> for Throttle<H>
+where
+    H: Handle<'a, Context>,
+    Context: MaybeSend + 'a,
+    H::Output: 'a,
+{
+    type Output = H::Output;
+
+    fn call(&'a self, cx: &'a mut Context) -> BoxFuture<'a, Self::Output> {
+        Box::pin(async move {
+            let _permit = self
+                .semaphore
+                .acquire()
+                .await
+                .expect("the semaphore of `Throttle` is never closed");
+            self.h.call(cx).await
+        })
+    }
+
+    #[inline]
+    fn name(&self) -> &str {
+        self.h.name()
+    }
+}
+
+// Spawns the calls on a multi-threaded runtime, which needs `send`.
+#[cfg(all(test, feature = "send"))]
+mod tests {
+    use crate::{Handle, Throttle};
+    use std::{
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc,
+        },
+        time::Duration,
+    };
+    use tokio::time::sleep;
+
+    #[derive(Clone, Default)]
+    struct Context {
+        running: Arc<AtomicUsize>,
+        peak: Arc<AtomicUsize>,
+    }
+
+    async fn work(cx: &mut Context) -> usize {
+        let running = cx.running.fetch_add(1, Ordering::SeqCst) + 1;
+        cx.peak.fetch_max(running, Ordering::SeqCst);
+        sleep(Duration::from_millis(5)).await;
+        cx.running.fetch_sub(1, Ordering::SeqCst);
+        running
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn limits_concurrency() {
+        let h = Arc::new(Throttle::new(work, 10));
+        let cx = Context::default();
+
+        let tasks: Vec<_> = (0..100)
+            .map(|_| {
+                let (h, mut cx) = (h.clone(), cx.clone());
+                tokio::spawn(async move { h.call(&mut cx).await })
+            })
+            .collect();
+        for task in tasks {
+            assert!(task.await.unwrap() <= 10);
+        }
+
+        assert_eq!(cx.peak.load(Ordering::SeqCst), 10);
+        assert_eq!(cx.running.load(Ordering::SeqCst), 0);
+        assert_eq!(h.available(), 10);
+    }
+}
@@ -0,0 +1,416 @@
+use std::{
+    fmt,
+    marker::PhantomData,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex, OnceLock, PoisonError,
+    },
+    time::{Duration, Instant},
+};
+
+use crate::{
+    next::OnNext, BoxFuture, ContextExt, Empty, FromNextAlreadyCalled, Handle, MaybeSend,
+    MaybeSync, Pipeline,
+};
+
+/// Measures the wall-clock duration of the handler's last call.
+///
+/// The duration includes everything the handler awaits, so for a middleware it
+/// also covers the rest of the pipeline.
+///
+/// The duration is stored in nanoseconds with [`Ordering::Relaxed`]: a reader
+/// always sees a whole duration of some completed call, but no ordering with
+/// other memory is implied. Reading it after the run has been awaited, on the
+/// same task, observes the latest call.
+#[derive(Debug, Clone)]
+pub struct TimedHandle<H> {
+    h: H,
+    elapsed: Arc<AtomicU64>,
+}
+
+impl<H> TimedHandle<H> {
+    /// Creates a new [`TimedHandle`].
+    #[inline]
+    pub fn new(h: H) -> Self {
+        Self {
+            h,
+            elapsed: Arc::default(),
+        }
+    }
+
+    /// Returns the duration of the last call, zero if it was never called.
+    #[inline]
+    pub fn elapsed(&self) -> Duration {
+        Duration::from_nanos(self.elapsed.load(Ordering::Relaxed))
+    }
+}
+
+impl<'a, Context, H> Handle<'a, Context> for TimedHandle<H>
+where
+    H: Handle<'a, Context>,
+    Context: 'a,
+{
+    type Output = H::Output;
+
+    fn call(&'a self, cx: &'a mut Context) -> BoxFuture<'a, Self::Output> {
+        let start = Instant::now();
+        let fut = self.h.call(cx);
+
+        Box::pin(async move {
+            let output = fut.await;
+            let nanos = u64::try_from(start.elapsed().as_nanos()).unwrap_or(u64::MAX);
+            self.elapsed.store(nanos, Ordering::Relaxed);
+            output
+        })
+    }
+}
+
+/// A sink receiving the durations measured by [`Timed`] handlers.
+///
+/// An `Arc<Mutex<Vec<(String, Duration)>>>` collects the totals in the order
+/// they are recorded.
+pub trait Timings: MaybeSend + MaybeSync + 'static {
+    /// Records the duration of a whole call of the handler named `name`,
+    /// including the rest of the pipeline.
+    fn record(&self, name: &str, total: Duration);
+
+    /// Records the durations of a call of the handler named `name`: until it
+    /// called [`ContextExt::next`], the whole call if it did not, and in
+    /// `total`.
+    ///
+    /// Defaults to [`Timings::record`], dropping `before_next`.
+    fn record_split(&self, name: &str, before_next: Duration, total: Duration) {
+        let _ = before_next;
+        self.record(name, total);
+    }
+}
+
+impl Timings for Arc<Mutex<Vec<(String, Duration)>>> {
+    fn record(&self, name: &str, total: Duration) {
+        self.lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .push((name.to_string(), total));
+    }
+}
+
+/// Reports the durations of each call of the handler into a [`Timings`] sink.
+///
+/// The cursor of the pipeline notes when the handler calls
+/// [`ContextExt::next`], nothing is added to the pipeline.
+///
+/// Since a middleware completes after the rest of the pipeline, inner handlers
+/// are recorded before outer ones.
+#[derive(Debug, Clone)]
+pub struct Timed<H, S> {
+    h: H,
+    name: &'static str,
+    sink: S,
+}
+
+impl<H, S> Timed<H, S> {
+    /// Creates a new [`Timed`].
+    #[inline]
+    pub const fn new(h: H, name: &'static str, sink: S) -> Self {
+        Self { h, name, sink }
+    }
+}
+
+impl<'a, Context, Output, H, S> Handle<'a, Context> for Timed<H, S>
+where
+    H: for<'b> Handle<'b, Context, Output = Output>,
+    S: Timings,
+    Context: ContextExt<Output>,
+    Output: 'static,
+{
+    type Output = Output;
+
+    fn call(&'a self, cx: &'a mut Context) -> BoxFuture<'a, Self::Output> {
+        Box::pin(async move {
+            let start = Instant::now();
+            let before_next = Arc::new(OnceLock::new());
+
+            // A wrapping handler calls next at the same time, so its probe
+            // runs as well.
+            let prev = cx.next_mut().on_next.take();
+            let probe = before_next.clone();
+            let outer = prev.clone();
+            cx.next_mut().on_next = Some(Arc::new(move || {
+                let _ = probe.set(start.elapsed());
+                if let Some(f) = &outer {
+                    f();
+                }
+            }));
+
+            let guard = Restore {
+                cx,
+                prev,
+                _output: PhantomData,
+            };
+            let output = self.h.call(&mut *guard.cx).await;
+            drop(guard);
+            let total = start.elapsed();
+
+            let before_next = before_next.get().copied().unwrap_or(total);
+            self.sink.record_split(self.name, before_next, total);
+            output
+        })
+    }
+}
+
+/// Puts back the probe of the wrapping handler when the handler of a [`Timed`]
+/// did not call next, even if its call is dropped.
+struct Restore<'a, Context, Output>
+where
+    Context: ContextExt<Output>,
+{
+    cx: &'a mut Context,
+    prev: Option<OnNext>,
+    _output: PhantomData<fn() -> Output>,
+}
+
+impl<Context, Output> Drop for Restore<'_, Context, Output>
+where
+    Context: ContextExt<Output>,
+{
+    fn drop(&mut self) {
+        let next = self.cx.next_mut();
+        if next.on_next.is_some() {
+            next.on_next = self.prev.take();
+        }
+    }
+}
+
+/// A [`Pipeline`] wrapping each handler in a [`TimedHandle`].
+pub struct TimedPipeline<Context, Output> {
+    pipeline: Pipeline<Context, Output>,
+    timings: Vec<(String, Arc<AtomicU64>)>,
+}
+
+impl<Context, Output> TimedPipeline<Context, Output> {
+    /// Creates an empty [`TimedPipeline`].
+    #[inline]
+    pub fn new() -> Self {
+        Self {
+            pipeline: Pipeline::new(),
+            timings: Vec::new(),
+        }
+    }
+
+    /// Appends a handler named `name` to the end of the pipeline.
+    pub fn push<H>(&mut self, name: impl Into<String>, h: H) -> &mut Self
+    where
+        H: for<'a> Handle<'a, Context, Output = Output>,
+        Context: 'static,
+        Output: 'static,
+    {
+        let h = TimedHandle::new(h);
+        self.timings.push((name.into(), h.elapsed.clone()));
+        self.pipeline.push(h);
+        self
+    }
+
+    /// Runs the pipeline on the context.
+    #[inline]
+    pub fn run<'a>(&self, cx: &'a mut Context) -> BoxFuture<'a, Output>
+    where
+        Context: ContextExt<Output>,
+        Output: Empty + FromNextAlreadyCalled + 'static,
+    {
+        self.pipeline.run(cx)
+    }
+
+    /// Returns the name and the last duration of each handler, in order.
+    pub fn execution_report(&self) -> Vec<(String, Duration)> {
+        self.timings
+            .iter()
+            .map(|(name, nanos)| {
+                (
+                    name.clone(),
+                    Duration::from_nanos(nanos.load(Ordering::Relaxed)),
+                )
+            })
+            .collect()
+    }
+}
+
+impl<Context, Output> Default for TimedPipeline<Context, Output> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<Context, Output> fmt::Debug for TimedPipeline<Context, Output>
+where
+    Context: 'static,
+    Output: 'static,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TimedPipeline")
+            .field("pipeline", &self.pipeline)
+            .field("timings", &self.execution_report())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        ContextExt, Handle, HandleExt, Next, Pipeline, TimedHandle, TimedPipeline, Timings,
+    };
+    use async_std::task::sleep;
+    use std::{
+        sync::{Arc, Mutex},
+        time::Duration,
+    };
+
+    type Result = anyhow::Result<()>;
+
+    #[derive(Default)]
+    struct Context {
+        next: Next<Self, Result>,
+    }
+
+    impl ContextExt<Result> for Context {
+        fn next_mut(&mut self) -> &mut Next<Self, Result> {
+            &mut self.next
+        }
+
+        fn next_ref(&self) -> &Next<Self, Result> {
+            &self.next
+        }
+    }
+
+    async fn outer(cx: &mut Context) -> Result {
+        sleep(Duration::from_millis(5)).await;
+        cx.next().await
+    }
+
+    async fn inner(cx: &mut Context) -> Result {
+        sleep(Duration::from_millis(5)).await;
+        cx.next().await
+    }
+
+    async fn last(_: &mut Context) -> Result {
+        sleep(Duration::from_millis(5)).await;
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn timed_handle() -> Result {
+        let h = TimedHandle::new(inner);
+        assert_eq!(h.elapsed(), Duration::ZERO);
+
+        let mut cx = Context::default();
+        h.call(&mut cx).await?;
+        assert!(h.elapsed() >= Duration::from_millis(5));
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn execution_report() -> Result {
+        let mut pipeline = TimedPipeline::new();
+        pipeline.push("outer", outer).push("inner", inner);
+
+        let mut cx = Context::default();
+        pipeline.run(&mut cx).await?;
+
+        let report = pipeline.execution_report();
+        assert_eq!(report[0].0, "outer");
+        assert_eq!(report[1].0, "inner");
+        assert!(report[1].1 >= Duration::from_millis(5));
+        assert!(report[0].1 >= report[1].1);
+
+        Ok(())
+    }
+
+    #[derive(Clone, Default)]
+    struct Splits(Arc<Mutex<Vec<(String, Duration, Duration)>>>);
+
+    impl Timings for Splits {
+        fn record(&self, _: &str, _: Duration) {
+            unreachable!("`Timed` records the split durations");
+        }
+
+        fn record_split(&self, name: &str, before_next: Duration, total: Duration) {
+            let split = (name.to_string(), before_next, total);
+            self.0.lock().unwrap().push(split);
+        }
+    }
+
+    #[async_std::test]
+    async fn timed_sink() -> Result {
+        let sink = Arc::new(Mutex::new(Vec::new()));
+
+        let mut pipeline = Pipeline::new();
+        pipeline
+            .push(outer.timed("outer", sink.clone()))
+            .push(inner.timed("inner", sink.clone()))
+            .push(last.timed("last", sink.clone()));
+
+        let mut cx = Context::default();
+        pipeline.run(&mut cx).await?;
+
+        let entries = sink.lock().unwrap();
+        let names: Vec<_> = entries.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(names, ["last", "inner", "outer"]);
+        assert!(entries[0].1 >= Duration::from_millis(5));
+        assert!(entries[1].1 >= entries[0].1);
+        assert!(entries[2].1 >= entries[1].1);
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn timed_split() -> Result {
+        let sink = Splits::default();
+
+        let mut pipeline = Pipeline::new();
+        pipeline
+            .push(outer.timed("outer", sink.clone()))
+            .push(inner.timed("inner", sink.clone()))
+            .push(last.timed("last", sink.clone()));
+
+        let mut cx = Context::default();
+        pipeline.run(&mut cx).await?;
+        assert!(cx.next.on_next.is_none());
+
+        let entries = sink.0.lock().unwrap();
+        // `last` does not call `next`, its whole call is before it.
+        assert_eq!(entries[0].1, entries[0].2);
+        for (_, before_next, _) in &entries[1..] {
+            assert!(*before_next >= Duration::from_millis(5));
+        }
+        // The rest of the pipeline runs between `next` and the end.
+        assert!(entries[2].2 - entries[2].1 >= entries[1].2);
+        assert!(entries[1].2 - entries[1].1 >= entries[0].2);
+
+        Ok(())
+    }
+
+    #[async_std::test]
+    async fn timed_twice() -> Result {
+        let sink = Splits::default();
+
+        let mut pipeline = Pipeline::new();
+        pipeline
+            .push(
+                outer
+                    .timed("inner", sink.clone())
+                    .timed("outer", sink.clone()),
+            )
+            .push(last);
+
+        let mut cx = Context::default();
+        pipeline.run(&mut cx).await?;
+
+        // Both wrappers see the same call of `next`.
+        let entries = sink.0.lock().unwrap();
+        assert_eq!(entries[0].0, "inner");
+        assert!(entries[0].1 >= Duration::from_millis(5));
+        assert!(entries[1].1 >= entries[0].1);
+        assert!(entries[1].2 - entries[1].1 >= Duration::from_millis(5));
+
+        Ok(())
+    }
+}
@@ -0,0 +1,180 @@
+//! Statically dispatched pipelines of tuples.
+//!
+//! A tuple of 2 to 12 handlers with the same output runs all of them in order
+//! on the same context, and the output is the last one's. [`TryTuple`] runs
+//! them like [`hpipeline!`](crate::hpipeline) instead: each one runs only if
+//! the previous one succeeded.
+//!
+//! The handlers are called directly, without any [`ArcHandle`] indirection,
+//! so the pipeline is a plain value whose type lists its handlers. It is not
+//! faster for it: every [`Handle::call`] still returns a [`BoxFuture`], and
+//! the `tuple` bench runs on par with a `Vec` of [`ArcHandle`]s.
+//!
+//! The handlers share the cursor of the pipeline: only the first one calling
+//! [`ContextExt::next`] runs the rest of it, the following ones get the
+//! [`NextAlreadyCalled`] error.
+//!
+//! [`ContextExt::next`]: crate::ContextExt::next
+//! [`NextAlreadyCalled`]: crate::NextAlreadyCalled
+//! [`ArcHandle`]: crate::ArcHandle
+
+use alloc::boxed::Box;
+
+use crate::{BoxFuture, Handle, MaybeSend, MaybeSync};
+
+/// A tuple of handlers returning [`Result`]s, stopping at the first error.
+///
+/// See the [module](self) documentation.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TryTuple<T>(T);
+
+impl<T> TryTuple<T> {
+    /// Creates a new [`TryTuple`] of the handlers of `t`.
+    #[inline]
+    pub const fn new(t: T) -> Self {
+        Self(t)
+    }
+
+    /// Returns the handlers as a tuple.
+    #[inline]
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+macro_rules! tuple_handle {
+    ($($h:ident $index:tt),+; $last:ident $last_index:tt) => {
+        impl<'a, Context, O, $($h,)+ $last> Handle<'a, Context> for ($($h,)+ $last)
+        where
+            $($h: for<'b> Handle<'b, Context, Output = O>,)+
+            $last: Handle<'a, Context, Output = O>,
+            Context: MaybeSend + 'a,
+        {
+            type Output = O;
+
+            fn call(&'a self, cx: &'a mut Context) -> BoxFuture<'a, Self::Output> {
+                Box::pin(async move {
+                    $(self.$index.call(&mut *cx).await;)+
+                    self.$last_index.call(cx).await
+                })
+            }
+        }
+
+        impl<'a, Context, T, E, $($h,)+ $last> Handle<'a, Context> for TryTuple<($($h,)+ $last)>
+        where
+            $($h: for<'b> Handle<'b, Context, Output = Result<T, E>>,)+
+            $last: Handle<'a, Context, Output = Result<T, E>>,
+            ($($h,)+ $last): MaybeSend + MaybeSync + 'static,
+            Context: MaybeSend + 'a,
+            T: MaybeSend + 'a,
+            E: MaybeSend + 'a,
+        {
+            type Output = Result<T, E>;
+
+            fn call(&'a self, cx: &'a mut Context) -> BoxFuture<'a, Self::Output> {
+                Box::pin(async move {
+                    $(self.0.$index.call(&mut *cx).await?;)+
+                    self.0.$last_index.call(cx).await
+                })
+            }
+        }
+    };
+}
+
+tuple_handle!(H0 0; H1 1);
+tuple_handle!(H0 0, H1 1; H2 2);
+tuple_handle!(H0 0, H1 1, H2 2; H3 3);
+tuple_handle!(H0 0, H1 1, H2 2, H3 3; H4 4);
+tuple_handle!(H0 0, H1 1, H2 2, H3 3, H4 4; H5 5);
+tuple_handle!(H0 0, H1 1, H2 2, H3 3, H4 4, H5 5; H6 6);
+tuple_handle!(H0 0, H1 1, H2 2, H3 3, H4 4, H5 5, H6 6; H7 7);
+tuple_handle!(H0 0, H1 1, H2 2, H3 3, H4 4, H5 5, H6 6, H7 7; H8 8);
+tuple_handle!(H0 0, H1 1, H2 2, H3 3, H4 4, H5 5, H6 6, H7 7, H8 8; H9 9);
+tuple_handle!(H0 0, H1 1, H2 2, H3 3, H4 4, H5 5, H6 6, H7 7, H8 8, H9 9; H10 10);
+tuple_handle!(H0 0, H1 1, H2 2, H3 3, H4 4, H5 5, H6 6, H7 7, H8 8, H9 9, H10 10; H11 11);
+
+#[cfg(test)]
+mod tests {
+    use crate::{Handle, TryTuple};
+    use futures::executor::block_on;
+
+    type Result = std::result::Result<usize, &'static str>;
+
+    #[derive(Default)]
+    struct Context {
+        fail: bool,
+        trace: Vec<&'static str>,
+    }
+
+    async fn auth(cx: &mut Context) -> Result {
+        cx.trace.push("auth");
+        if cx.fail {
+            return Err("auth");
+        }
+        Ok(cx.trace.len())
+    }
+
+    async fn log(cx: &mut Context) -> Result {
+        cx.trace.push("log");
+        Ok(cx.trace.len())
+    }
+
+    async fn business(cx: &mut Context) -> Result {
+        cx.trace.push("business");
+        Ok(cx.trace.len())
+    }
+
+    async fn count(cx: &mut Context) -> usize {
+        cx.trace.push("count");
+        cx.trace.len()
+    }
+
+    #[test]
+    fn runs_in_order() {
+        let pipeline = (auth, log, business);
+
+        let mut cx = Context::default();
+        assert_eq!(block_on(pipeline.call(&mut cx)), Ok(3));
+        assert_eq!(cx.trace, ["auth", "log", "business"]);
+
+        // Every handler runs, the output is the last one's.
+        let mut cx = Context {
+            fail: true,
+            ..Default::default()
+        };
+        assert_eq!(block_on(pipeline.call(&mut cx)), Ok(3));
+        assert_eq!(cx.trace, ["auth", "log", "business"]);
+    }
+
+    #[test]
+    fn any_output() {
+        let pipeline = (count, count, count);
+
+        let mut cx = Context::default();
+        assert_eq!(block_on(pipeline.call(&mut cx)), 3);
+    }
+
+    #[test]
+    fn try_tuple() {
+        let pipeline = TryTuple::new((auth, log, business));
+
+        let mut cx = Context::default();
+        assert_eq!(block_on(pipeline.call(&mut cx)), Ok(3));
+        assert_eq!(cx.trace, ["auth", "log", "business"]);
+
+        let mut cx = Context {
+            fail: true,
+            ..Default::default()
+        };
+        assert_eq!(block_on(pipeline.call(&mut cx)), Err("auth"));
+        assert_eq!(cx.trace, ["auth"]);
+    }
+
+    #[test]
+    fn nested() {
+        let pipeline = (log, (log, log), log, log, log, log, log, log, log, log, log);
+
+        let mut cx = Context::default();
+        assert_eq!(block_on(pipeline.call(&mut cx)), Ok(12));
+    }
+}
@@ -0,0 +1,177 @@
+use alloc::boxed::Box;
+use core::ops::ControlFlow;
+
+use crate::{BoxFuture, ContextExt, Empty, FromNextAlreadyCalled, Handle, MaybeSend};
+
+/// An output which tells [`UntilBreak`] whether to continue the pipeline.
+pub trait IntoControlFlow {
+    /// The value breaking the pipeline, which becomes its output.
+    type Break;
+    /// The value continuing the pipeline, which is dropped.
+    type Continue;
+
+    /// Converts the output into a [`ControlFlow`].
+    fn into_control_flow(self) -> ControlFlow<Self::Break, Self::Continue>;
+}
+
+impl<B, C> IntoControlFlow for ControlFlow<B, C> {
+    type Break = B;
+    type Continue = C;
+
+    #[inline]
+    fn into_control_flow(self) -> ControlFlow<B, C> {
+        self
+    }
+}
+
+/// An `Err` breaks, an `Ok` continues.
+impl<T, E> IntoControlFlow for Result<T, E> {
+    type Break = E;
+    type Continue = T;
+
+    #[inline]
+    fn into_control_flow(self) -> ControlFlow<E, T> {
+        match self {
+            Ok(t) => ControlFlow::Continue(t),
+            Err(e) => ControlFlow::Break(e),
+        }
+    }
+}
+
+/// Continues the pipeline while the handler returns [`ControlFlow::Continue`].
+///
+/// The handler does not call [`ContextExt::next`] itself: on `Continue` the
+/// next handler of the pipeline is called and its output is returned, on
+/// `Break(b)` the remaining handlers are skipped and `b` becomes the output of
+/// the pipeline. An exhausted pipeline returns [`Empty::empty`].
+///
+/// The outputs are read with [`IntoControlFlow`], so a `Result<T, E>` output
+/// maps onto `ControlFlow<E, T>`: an `Err(e)` breaks with `e`, an `Ok`
+/// continues.
+#[derive(Debug, Clone)]
+pub struct UntilBreak<H> {
+    h: H,
+}
+
+impl<H> UntilBreak<H> {
+    /// Creates a new [`UntilBreak`].
+    #[inline]
+    pub const fn new(h: H) -> Self {
+        Self { h }
+    }
+}
+
+impl<'a, Context, H, O> Handle<'a, Context> for UntilBreak<H>
+where
+    H: for<'b> Handle<'b, Context, Output = O>,
+    O: IntoControlFlow,
+    Context: ContextExt<O::Break>,
+    O::Break: Empty + FromNextAlreadyCalled + MaybeSend + 'static,
+{
+    type Output = O::Break;
+
+    fn call(&'a self, cx: &'a mut Context) -> BoxFuture<'a, Self::Output> {
+        Box::pin(async move {
+            // The continue value is dropped before the rest of the pipeline.
+            if let ControlFlow::Break(b) = self.h.call(&mut *cx).await.into_control_flow() {
+                return b;
+            }
+            cx.next().await
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ContextExt, HandleExt, Next, Pipeline};
+    use futures::executor::block_on;
+    use std::ops::ControlFlow;
+
+    type Output = Option<u16>;
+
+    #[derive(Default)]
+    struct Context {
+        trace: Vec<&'static str>,
+        cached: bool,
+        denied: bool,
+        next: Next<Self, Output>,
+    }
+
+    impl ContextExt<Output> for Context {
+        fn next_mut(&mut self) -> &mut Next<Self, Output> {
+            &mut self.next
+        }
+
+        fn next_ref(&self) -> &Next<Self, Output> {
+            &self.next
+        }
+    }
+
+    async fn log(cx: &mut Context) -> ControlFlow<Output> {
+        cx.trace.push("log");
+        ControlFlow::Continue(())
+    }
+
+    async fn cache(cx: &mut Context) -> ControlFlow<Output> {
+        cx.trace.push("cache");
+        if cx.cached {
+            ControlFlow::Break(Some(304))
+        } else {
+            ControlFlow::Continue(())
+        }
+    }
+
+    async fn auth(cx: &mut Context) -> Result<(), Output> {
+        cx.trace.push("auth");
+        if cx.denied {
+            Err(Some(403))
+        } else {
+            Ok(())
+        }
+    }
+
+    async fn render(cx: &mut Context) -> ControlFlow<Output> {
+        cx.trace.push("render");
+        ControlFlow::Break(Some(200))
+    }
+
+    fn run(cached: bool, denied: bool) -> (Output, Vec<&'static str>) {
+        let mut pipeline = Pipeline::new();
+        pipeline
+            .push(log.until_break())
+            .push(cache.until_break())
+            .push(auth.until_break())
+            .push(render.until_break());
+
+        let mut cx = Context {
+            cached,
+            denied,
+            ..Default::default()
+        };
+        let output = block_on(pipeline.run(&mut cx));
+        (output, cx.trace)
+    }
+
+    #[test]
+    fn until_break() {
+        assert_eq!(
+            run(false, false),
+            (Some(200), vec!["log", "cache", "auth", "render"])
+        );
+        assert_eq!(run(true, false), (Some(304), vec!["log", "cache"]));
+    }
+
+    #[test]
+    fn err_breaks() {
+        assert_eq!(run(false, true), (Some(403), vec!["log", "cache", "auth"]));
+    }
+
+    #[test]
+    fn exhausted() {
+        let mut pipeline = Pipeline::new();
+        pipeline.push(log.until_break());
+
+        let mut cx = Context::default();
+        assert_eq!(block_on(pipeline.run(&mut cx)), None);
+    }
+}
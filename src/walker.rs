@@ -0,0 +1,166 @@
+use alloc::{sync::Arc, vec::Vec};
+use core::{fmt, mem};
+
+use crate::{ArcHandle, ContextExt, Empty, FromNextAlreadyCalled, Pipeline};
+
+/// Steps through the handlers of a pipeline one at a time, see
+/// [`Pipeline::walker`].
+///
+/// Each handler runs as a leaf with an empty cursor: when it calls
+/// [`ContextExt::next`], it gets [`Empty::empty`] instead of running the rest
+/// of the pipeline, which the following steps run one by one. The walker
+/// reports it with [`PipelineWalker::reentered`]. The hooks are not called.
+pub struct PipelineWalker<'cx, Context, Output> {
+    cx: &'cx mut Context,
+    handlers: Arc<[ArcHandle<Context, Output>]>,
+    index: usize,
+    reentered: bool,
+}
+
+impl<'cx, Context, Output> PipelineWalker<'cx, Context, Output> {
+    pub(crate) fn new(handlers: Arc<[ArcHandle<Context, Output>]>, cx: &'cx mut Context) -> Self {
+        Self {
+            cx,
+            handlers,
+            index: 0,
+            reentered: false,
+        }
+    }
+
+    /// Runs the next handler and returns its output, or `None` once all the
+    /// handlers have run.
+    pub async fn step(&mut self) -> Option<Output>
+    where
+        Context: ContextExt<Output>,
+        Output: Empty + FromNextAlreadyCalled + 'static,
+    {
+        let h = self.handlers.get(self.index)?.clone();
+        self.index += 1;
+
+        let prev = mem::take(self.cx.next_mut());
+        let output = h.call(&mut *self.cx).await;
+        let leaf = mem::replace(self.cx.next_mut(), prev);
+        self.reentered = leaf.called();
+        Some(output)
+    }
+
+    /// Returns `true` if the handler of the last step called
+    /// [`ContextExt::next`].
+    #[inline]
+    pub fn reentered(&self) -> bool {
+        self.reentered
+    }
+
+    /// Returns the number of handlers left.
+    #[inline]
+    pub fn remaining(&self) -> usize {
+        self.handlers.len() - self.index
+    }
+
+    /// Returns the names of the handlers left.
+    pub fn peek_names(&self) -> Vec<&str>
+    where
+        Context: 'static,
+        Output: 'static,
+    {
+        self.handlers[self.index..]
+            .iter()
+            .map(|h| h.name())
+            .collect()
+    }
+
+    /// Returns the context, to inspect it between the steps.
+    #[inline]
+    pub fn context(&self) -> &Context {
+        self.cx
+    }
+
+    /// Returns the context, to change it between the steps.
+    #[inline]
+    pub fn context_mut(&mut self) -> &mut Context {
+        self.cx
+    }
+}
+
+impl<Context, Output> Pipeline<Context, Output> {
+    /// Returns a walker running the handlers on the context one step at a
+    /// time, see [`PipelineWalker`].
+    pub fn walker<'cx>(&self, cx: &'cx mut Context) -> PipelineWalker<'cx, Context, Output> {
+        PipelineWalker::new(self.iter().cloned().collect(), cx)
+    }
+}
+
+impl<Context, Output> fmt::Debug for PipelineWalker<'_, Context, Output> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PipelineWalker")
+            .field("len", &self.handlers.len())
+            .field("index", &self.index)
+            .field("reentered", &self.reentered)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ContextExt, Next, Pipeline};
+    use futures::executor::block_on;
+
+    type Result = std::result::Result<(), &'static str>;
+
+    #[derive(Default)]
+    struct Context {
+        auth_set: bool,
+        trace: Vec<&'static str>,
+        next: Next<Self, Result>,
+    }
+
+    impl ContextExt<Result> for Context {
+        fn next_mut(&mut self) -> &mut Next<Self, Result> {
+            &mut self.next
+        }
+
+        fn next_ref(&self) -> &Next<Self, Result> {
+            &self.next
+        }
+    }
+
+    async fn log(cx: &mut Context) -> Result {
+        cx.trace.push("log>");
+        let output = cx.next().await;
+        cx.trace.push("log<");
+        output
+    }
+
+    async fn auth(cx: &mut Context) -> Result {
+        cx.auth_set = true;
+        Ok(())
+    }
+
+    async fn deny(_: &mut Context) -> Result {
+        Err("denied")
+    }
+
+    #[test]
+    fn steps() {
+        let mut pipeline = Pipeline::new();
+        pipeline.push(log).push(auth).push(deny);
+
+        let mut cx = Context::default();
+        let mut walker = pipeline.walker(&mut cx);
+        block_on(async {
+            assert_eq!(walker.remaining(), 3);
+            assert_eq!(walker.step().await, Some(Ok(())));
+            assert!(walker.reentered());
+            assert_eq!(walker.context().trace, ["log>", "log<"]);
+            assert!(!walker.context().auth_set);
+
+            assert_eq!(walker.step().await, Some(Ok(())));
+            assert!(!walker.reentered());
+            assert!(walker.context().auth_set);
+
+            assert_eq!(walker.step().await, Some(Err("denied")));
+            assert_eq!(walker.step().await, None);
+            assert_eq!(walker.remaining(), 0);
+        });
+    }
+}
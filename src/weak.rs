@@ -0,0 +1,165 @@
+use alloc::{
+    boxed::Box,
+    sync::{Arc, Weak},
+};
+use core::fmt;
+
+use crate::{
+    ArcHandle, BoxFuture, ContextExt, DynHandle, Empty, FromNextAlreadyCalled, Handle, MaybeSend,
+};
+
+type WeakDyn<Context, Output> = Weak<DynHandle<Context, Output>>;
+
+type Absent<Context, Output> = for<'cx> fn(&'cx mut Context) -> BoxFuture<'cx, Output>;
+
+/// A handler which does not keep the handler it points to alive.
+///
+/// Once the handler is dropped, the calls return the absent output instead,
+/// [`Empty::empty`] by default, and [`Pipeline::prune`](crate::Pipeline::prune)
+/// removes the dead handlers from a pipeline.
+pub struct WeakHandle<Context, Output> {
+    h: WeakDyn<Context, Output>,
+    absent: Absent<Context, Output>,
+}
+
+impl<Context, Output> WeakHandle<Context, Output> {
+    /// Creates a new [`WeakHandle`] pointing to `h`.
+    pub fn downgrade(h: &ArcHandle<Context, Output>) -> Self
+    where
+        Output: Empty + MaybeSend + 'static,
+    {
+        Self {
+            h: Arc::downgrade(h),
+            absent: |_| Box::pin(async { Output::empty() }),
+        }
+    }
+
+    /// Calls `f` instead once the handler is dropped.
+    #[must_use]
+    pub fn or_else(mut self, f: Absent<Context, Output>) -> Self {
+        self.absent = f;
+        self
+    }
+
+    /// Continues the pipeline once the handler is dropped, as if it had been
+    /// removed.
+    #[must_use]
+    pub fn or_next(self) -> Self
+    where
+        Context: ContextExt<Output>,
+        Output: Empty + FromNextAlreadyCalled + 'static,
+    {
+        self.or_else(|cx| cx.next())
+    }
+
+    /// Returns `true` if the handler has not been dropped.
+    #[inline]
+    pub fn is_alive(&self) -> bool {
+        self.h.strong_count() > 0
+    }
+}
+
+impl<'a, Context, Output> Handle<'a, Context> for WeakHandle<Context, Output>
+where
+    Context: MaybeSend + 'static,
+    Output: MaybeSend + 'static,
+{
+    type Output = Output;
+
+    fn call(&'a self, cx: &'a mut Context) -> BoxFuture<'a, Self::Output> {
+        match self.h.upgrade() {
+            // The future keeps the handler alive until it completes.
+            Some(h) => Box::pin(async move { h.call(cx).await }),
+            None => (self.absent)(cx),
+        }
+    }
+}
+
+impl<Context, Output> Clone for WeakHandle<Context, Output> {
+    fn clone(&self) -> Self {
+        Self {
+            h: self.h.clone(),
+            absent: self.absent,
+        }
+    }
+}
+
+impl<Context, Output> fmt::Debug for WeakHandle<Context, Output> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WeakHandle")
+            .field("alive", &self.is_alive())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ArcHandle, ContextExt, Handle, Next, Pipeline, WeakHandle};
+    use futures::executor::block_on;
+    use std::sync::Arc;
+
+    type Result = std::result::Result<(), &'static str>;
+
+    #[derive(Default)]
+    struct Context {
+        trace: Vec<&'static str>,
+        next: Next<Self, Result>,
+    }
+
+    impl ContextExt<Result> for Context {
+        fn next_mut(&mut self) -> &mut Next<Self, Result> {
+            &mut self.next
+        }
+
+        fn next_ref(&self) -> &Next<Self, Result> {
+            &self.next
+        }
+    }
+
+    async fn plugin(cx: &mut Context) -> Result {
+        cx.trace.push("plugin");
+        cx.next().await
+    }
+
+    async fn route(cx: &mut Context) -> Result {
+        cx.trace.push("route");
+        Ok(())
+    }
+
+    #[test]
+    fn skipped_once_dropped() {
+        let h: ArcHandle<Context, Result> = Arc::new(plugin);
+        let mut pipeline = Pipeline::new();
+        pipeline
+            .push(WeakHandle::downgrade(&h).or_next())
+            .push(route);
+
+        let mut cx = Context::default();
+        assert_eq!(block_on(pipeline.run(&mut cx)), Ok(()));
+        assert_eq!(cx.trace, ["plugin", "route"]);
+
+        drop(h);
+        let mut cx = Context::default();
+        assert_eq!(block_on(pipeline.run(&mut cx)), Ok(()));
+        assert_eq!(cx.trace, ["route"]);
+
+        assert_eq!(pipeline.prune(), 1);
+        assert_eq!(pipeline.len(), 1);
+    }
+
+    #[test]
+    fn absent_output() {
+        let h: ArcHandle<Context, Result> = Arc::new(route);
+        let weak = WeakHandle::downgrade(&h);
+        assert!(weak.is_alive());
+
+        drop(h);
+        let mut cx = Context::default();
+        assert!(!weak.is_alive());
+        assert_eq!(block_on(weak.call(&mut cx)), Ok(()));
+        assert!(cx.trace.is_empty());
+
+        let weak = weak.or_else(|_| Box::pin(async { Err("unloaded") }));
+        assert_eq!(block_on(weak.call(&mut cx)), Err("unloaded"));
+    }
+}
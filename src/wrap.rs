@@ -0,0 +1,265 @@
+//! Adapters turning plain async functions into pipeline handlers.
+//!
+//! They call [`ContextExt::next`] for you, so the function only does its own
+//! work before or after the rest of the pipeline. The `Output` parameter of the
+//! adapters is the output of the pipeline, it is inferred when pushed.
+
+use alloc::boxed::Box;
+use core::{fmt, future::Future, marker::PhantomData, mem};
+
+use crate::{
+    BoxFuture, ContextExt, Empty, FromNextAlreadyCalled, Handle, MaybeSend, MaybeSync, Next,
+    NextAlreadyCalled,
+};
+
+/// Runs `f`, then the rest of the pipeline if `f` succeeds.
+#[inline]
+pub const fn before<F, Output>(f: F) -> Before<F, Output> {
+    Before {
+        f,
+        _output: PhantomData,
+    }
+}
+
+/// Runs the rest of the pipeline, then `f` with the context and its output.
+#[inline]
+pub const fn after<F, Output>(f: F) -> After<F, Output> {
+    After {
+        f,
+        _output: PhantomData,
+    }
+}
+
+/// Runs `f` with the context and the [`Next`] cursor taken out of it.
+///
+/// The `f` continues the pipeline with [`Next::run`], or skips the rest of it
+/// by dropping the cursor. The context keeps a copy of the cursor meanwhile,
+/// so dropping it leaves the pipeline as if `f` had not called next.
+#[inline]
+pub const fn around<F, Output>(f: F) -> Around<F, Output> {
+    Around {
+        f,
+        _output: PhantomData,
+    }
+}
+
+/// The handler returned by [`before`].
+pub struct Before<F, Output> {
+    f: F,
+    _output: PhantomData<fn() -> Output>,
+}
+
+impl<F: Clone, Output> Clone for Before<F, Output> {
+    fn clone(&self) -> Self {
+        before(self.f.clone())
+    }
+}
+
+impl<F: fmt::Debug, Output> fmt::Debug for Before<F, Output> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Before").field("f", &self.f).finish()
+    }
+}
+
+impl<'a, Context, F, T, E> Handle<'a, Context> for Before<F, Result<T, E>>
+where
+    F: for<'b> Handle<'b, Context, Output = Result<(), E>>,
+    Context: ContextExt<Result<T, E>>,
+    T: Empty + MaybeSend + 'static,
+    E: From<NextAlreadyCalled> + MaybeSend + 'static,
+{
+    type Output = Result<T, E>;
+
+    fn call(&'a self, cx: &'a mut Context) -> BoxFuture<'a, Self::Output> {
+        Box::pin(async move {
+            self.f.call(&mut *cx).await?;
+            cx.next().await
+        })
+    }
+}
+
+/// The handler returned by [`after`].
+pub struct After<F, Output> {
+    f: F,
+    _output: PhantomData<fn() -> Output>,
+}
+
+impl<F: Clone, Output> Clone for After<F, Output> {
+    fn clone(&self) -> Self {
+        after(self.f.clone())
+    }
+}
+
+impl<F: fmt::Debug, Output> fmt::Debug for After<F, Output> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("After").field("f", &self.f).finish()
+    }
+}
+
+impl<'a, Context, F, Fut, Output> Handle<'a, Context> for After<F, Output>
+where
+    F: Fn(&'a mut Context, Output) -> Fut + MaybeSend + MaybeSync + 'static,
+    Fut: Future<Output = Output> + MaybeSend + 'a,
+    Context: ContextExt<Output>,
+    Output: Empty + FromNextAlreadyCalled + MaybeSend + 'static,
+{
+    type Output = Output;
+
+    fn call(&'a self, cx: &'a mut Context) -> BoxFuture<'a, Self::Output> {
+        Box::pin(async move {
+            let output = cx.next().await;
+            (self.f)(cx, output).await
+        })
+    }
+}
+
+/// The handler returned by [`around`].
+pub struct Around<F, Output> {
+    f: F,
+    _output: PhantomData<fn() -> Output>,
+}
+
+impl<F: Clone, Output> Clone for Around<F, Output> {
+    fn clone(&self) -> Self {
+        around(self.f.clone())
+    }
+}
+
+impl<F: fmt::Debug, Output> fmt::Debug for Around<F, Output> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Around").field("f", &self.f).finish()
+    }
+}
+
+impl<'a, Context, F, Fut, Output> Handle<'a, Context> for Around<F, Output>
+where
+    F: Fn(&'a mut Context, Next<Context, Output>) -> Fut + MaybeSend + MaybeSync + 'static,
+    Fut: Future<Output = Output> + MaybeSend + 'a,
+    Context: ContextExt<Output>,
+    Output: 'static,
+{
+    type Output = Output;
+
+    fn call(&'a self, cx: &'a mut Context) -> BoxFuture<'a, Self::Output> {
+        // `Next::run` puts the cursor back over the copy.
+        let copy = cx.next_ref().duplicate();
+        let next = mem::replace(cx.next_mut(), copy);
+        Box::pin((self.f)(cx, next))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{after, around, before};
+    use crate::{ContextExt, Next, Pipeline};
+    use anyhow::anyhow;
+    use futures::executor::block_on;
+
+    type Result = anyhow::Result<()>;
+
+    #[derive(Default)]
+    struct Context {
+        trace: Vec<&'static str>,
+        deny: bool,
+        seen: (usize, usize),
+        next: Next<Self, Result>,
+    }
+
+    impl ContextExt<Result> for Context {
+        fn next_mut(&mut self) -> &mut Next<Self, Result> {
+            &mut self.next
+        }
+
+        fn next_ref(&self) -> &Next<Self, Result> {
+            &self.next
+        }
+    }
+
+    async fn log(cx: &mut Context) -> Result {
+        cx.trace.push("log");
+        Ok(())
+    }
+
+    async fn auth(cx: &mut Context) -> Result {
+        cx.trace.push("auth");
+        if cx.deny {
+            Err(anyhow!("denied"))
+        } else {
+            Ok(())
+        }
+    }
+
+    async fn respond(cx: &mut Context, output: Result) -> Result {
+        cx.trace.push("respond");
+        output
+    }
+
+    async fn guard(cx: &mut Context, next: Next<Context, Result>) -> Result {
+        cx.trace.push("guard>");
+        let output = next.run(cx).await;
+        cx.trace.push("guard<");
+        output
+    }
+
+    async fn handler(cx: &mut Context) -> Result {
+        cx.trace.push("handler");
+        cx.next().await
+    }
+
+    fn pipeline() -> Pipeline<Context, Result> {
+        let mut pipeline = Pipeline::new();
+        pipeline
+            .push(after(respond))
+            .push(before(log))
+            .push(around(guard))
+            .push(before(auth))
+            .push(handler);
+        pipeline
+    }
+
+    #[test]
+    fn ordering() {
+        let mut cx = Context::default();
+        assert!(block_on(pipeline().run(&mut cx)).is_ok());
+        assert_eq!(
+            cx.trace,
+            ["log", "guard>", "auth", "handler", "guard<", "respond"]
+        );
+    }
+
+    async fn cached(cx: &mut Context, _: Next<Context, Result>) -> Result {
+        cx.trace.push("cached");
+        Ok(())
+    }
+
+    async fn outer(cx: &mut Context) -> Result {
+        cx.trace.push("outer>");
+        let output = cx.next().await;
+        let next = cx.next_ref();
+        cx.seen = (next.depth(), next.remaining());
+        cx.trace.push("outer<");
+        output
+    }
+
+    #[test]
+    fn dropped_cursor() {
+        let mut pipeline = Pipeline::new();
+        pipeline.push(outer).push(around(cached)).push(handler);
+
+        let mut cx = Context::default();
+        assert!(block_on(pipeline.run(&mut cx)).is_ok());
+        assert_eq!(cx.trace, ["outer>", "cached", "outer<"]);
+        // The cursor of `outer` is still running, `handler` was skipped.
+        assert_eq!(cx.seen, (1, 1));
+    }
+
+    #[test]
+    fn before_err() {
+        let mut cx = Context {
+            deny: true,
+            ..Default::default()
+        };
+        assert!(block_on(pipeline().run(&mut cx)).is_err());
+        assert_eq!(cx.trace, ["log", "guard>", "auth", "guard<", "respond"]);
+    }
+}
@@ -0,0 +1,20 @@
+#[test]
+fn handler() {
+    let t = trybuild::TestCases::new();
+    t.pass("tests/ui/handler/pass-*.rs");
+    t.compile_fail("tests/ui/handler/fail-*.rs");
+}
+
+#[test]
+fn derive() {
+    let t = trybuild::TestCases::new();
+    t.pass("tests/ui/derive/pass-*.rs");
+    t.compile_fail("tests/ui/derive/fail-*.rs");
+}
+
+#[test]
+fn meta() {
+    let t = trybuild::TestCases::new();
+    t.pass("tests/ui/meta/pass-*.rs");
+    t.compile_fail("tests/ui/meta/fail-*.rs");
+}
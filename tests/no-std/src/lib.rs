@@ -0,0 +1,94 @@
+//! Builds `handle` with `#![no_std]` and `alloc` only, and without the `send`
+//! feature:
+//!
+//! It is kept out of the workspace, so the features of the other members are
+//! not unified into its build of `handle`.
+//!
+//! ```sh
+//! cargo build --manifest-path tests/no-std/Cargo.toml
+//! cargo build --manifest-path tests/no-std/Cargo.toml --target thumbv7m-none-eabi
+//! ```
+
+#![no_std]
+
+extern crate alloc;
+
+use alloc::{rc::Rc, vec::Vec};
+use core::cell::Cell;
+use handle::{
+    ContextExt, DepthExceeded, Handle, HandleExt, Next, NextAlreadyCalled, Pipeline, Stack,
+};
+
+#[derive(Debug)]
+pub enum Error {
+    Failed,
+    TooDeep(DepthExceeded),
+    NextAlreadyCalled,
+}
+
+impl From<DepthExceeded> for Error {
+    fn from(e: DepthExceeded) -> Self {
+        Self::TooDeep(e)
+    }
+}
+
+impl From<NextAlreadyCalled> for Error {
+    fn from(_: NextAlreadyCalled) -> Self {
+        Self::NextAlreadyCalled
+    }
+}
+
+pub type Result = core::result::Result<(), Error>;
+
+#[derive(Default)]
+pub struct Context {
+    pub trace: Vec<&'static str>,
+    next: Next<Self, Result>,
+}
+
+impl ContextExt<Result> for Context {
+    fn next_mut(&mut self) -> &mut Next<Self, Result> {
+        &mut self.next
+    }
+
+    fn next_ref(&self) -> &Next<Self, Result> {
+        &self.next
+    }
+}
+
+async fn a(cx: &mut Context) -> Result {
+    cx.trace.push("a");
+    cx.next().await
+}
+
+async fn b(cx: &mut Context) -> Result {
+    cx.trace.push("b");
+    Err(Error::Failed)
+}
+
+async fn recover(cx: &mut Context) -> Result {
+    cx.trace.push("recover");
+    Ok(())
+}
+
+pub fn stack() -> Stack<Context, Result> {
+    let mut pipeline = Pipeline::new();
+    pipeline.push(a).push(b.fallback(recover)).max_depth(8);
+    pipeline.freeze()
+}
+
+pub async fn run(stack: &Stack<Context, Result>, cx: &mut Context) -> Result {
+    stack.run(cx).await
+}
+
+/// Without the `send` feature, a handler holding an `Rc` is a `Handle`.
+pub fn counter(hits: Rc<Cell<usize>>) -> impl for<'a> Handle<'a, Context, Output = Result> {
+    move |cx: &mut Context| {
+        let hits = hits.clone();
+        cx.trace.push("counter");
+        async move {
+            hits.set(hits.get() + 1);
+            Ok(())
+        }
+    }
+}
@@ -0,0 +1,14 @@
+#[derive(handle::Handle)]
+struct Named {
+    inner: fn(),
+}
+
+#[derive(handle::Handle)]
+struct Twice(#[handle(delegate)] fn(), #[handle(delegate)] fn());
+
+#[derive(handle::Handle)]
+enum Either {
+    Left,
+}
+
+fn main() {}
@@ -0,0 +1,39 @@
+use futures::executor::block_on;
+use handle::{BoxFuture, Handle};
+
+#[derive(Default)]
+struct Context {
+    trace: Vec<&'static str>,
+}
+
+struct Inner;
+
+impl<'a> Handle<'a, Context> for Inner {
+    type Output = usize;
+
+    fn call(&'a self, cx: &'a mut Context) -> BoxFuture<'a, Self::Output> {
+        Box::pin(async move {
+            cx.trace.push("inner");
+            cx.trace.len()
+        })
+    }
+}
+
+#[derive(Handle)]
+struct Configured {
+    label: &'static str,
+    #[handle(delegate)]
+    inner: Inner,
+}
+
+fn main() {
+    let h = Configured {
+        label: "inner",
+        inner: Inner,
+    };
+    assert_eq!(h.label, "inner");
+
+    let mut cx = Context::default();
+    assert_eq!(block_on(h.call(&mut cx)), 1);
+    assert_eq!(cx.trace, ["inner"]);
+}
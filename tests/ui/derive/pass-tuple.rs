@@ -0,0 +1,28 @@
+use futures::executor::block_on;
+use handle::{ArcHandle, Handle};
+use std::sync::Arc;
+
+#[derive(Default)]
+struct Context {
+    index: usize,
+}
+
+async fn add(cx: &mut Context) -> usize {
+    cx.index += 1;
+    cx.index
+}
+
+#[derive(Handle)]
+struct Logged<H>(H, &'static str);
+
+#[derive(Handle)]
+struct Metered<H>(&'static str, #[handle(delegate)] H);
+
+fn main() {
+    let mut cx = Context::default();
+    assert_eq!(block_on(Logged(add, "add").call(&mut cx)), 1);
+    assert_eq!(block_on(Metered("add", Logged(add, "add")).call(&mut cx)), 2);
+
+    let h: ArcHandle<Context, usize> = Arc::new(Logged(add, "add"));
+    assert_eq!(block_on(h.call(&mut cx)), 3);
+}
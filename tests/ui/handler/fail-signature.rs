@@ -0,0 +1,40 @@
+struct Context;
+
+struct NotAsync;
+
+#[handle::handler]
+impl NotAsync {
+    fn call(&self, cx: &mut Context) {}
+}
+
+struct MutSelf;
+
+#[handle::handler]
+impl MutSelf {
+    async fn call(&mut self, cx: &mut Context) {}
+}
+
+struct SharedContext;
+
+#[handle::handler]
+impl SharedContext {
+    async fn call(&self, cx: &Context) {}
+}
+
+struct WrongName;
+
+#[handle::handler]
+impl WrongName {
+    async fn handle(&self, cx: &mut Context) {}
+}
+
+struct TraitImpl;
+
+#[handle::handler]
+impl Default for TraitImpl {
+    fn default() -> Self {
+        Self
+    }
+}
+
+fn main() {}
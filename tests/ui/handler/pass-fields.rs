@@ -0,0 +1,34 @@
+use futures::executor::block_on;
+use handle::Handle;
+
+#[derive(Default)]
+struct Context {
+    index: usize,
+}
+
+struct Counter {
+    step: usize,
+}
+
+#[handle::handler]
+impl Counter {
+    async fn call(&self, cx: &mut Context) -> usize {
+        cx.index += self.step;
+        cx.index
+    }
+}
+
+struct Noop;
+
+#[handle::handler]
+impl Noop {
+    async fn call(&self, _: &mut Context) {}
+}
+
+fn main() {
+    let mut cx = Context::default();
+    let h = Counter { step: 2 };
+    assert_eq!(block_on(h.call(&mut cx)), 2);
+    assert_eq!(block_on(h.call(&mut cx)), 4);
+    block_on(Noop.call(&mut cx));
+}
@@ -0,0 +1,67 @@
+use futures::executor::block_on;
+use handle::{ContextExt, Handle, Next, Pipeline};
+use std::marker::PhantomData;
+
+type Result = anyhow::Result<()>;
+
+trait Trace {
+    fn trace(&mut self) -> &mut Vec<&'static str>;
+}
+
+struct Tag<C> {
+    name: &'static str,
+    _cx: PhantomData<fn(C)>,
+}
+
+#[handle::handler]
+impl<C> Tag<C>
+where
+    C: Trace + ContextExt<Result>,
+{
+    async fn call(&self, cx: &mut C) -> Result {
+        cx.trace().push(self.name);
+        cx.next().await
+    }
+}
+
+#[derive(Default)]
+struct Context {
+    trace: Vec<&'static str>,
+    next: Next<Self, Result>,
+}
+
+impl Trace for Context {
+    fn trace(&mut self) -> &mut Vec<&'static str> {
+        &mut self.trace
+    }
+}
+
+impl ContextExt<Result> for Context {
+    fn next_mut(&mut self) -> &mut Next<Self, Result> {
+        &mut self.next
+    }
+
+    fn next_ref(&self) -> &Next<Self, Result> {
+        &self.next
+    }
+}
+
+fn tag(name: &'static str) -> Tag<Context> {
+    Tag {
+        name,
+        _cx: PhantomData,
+    }
+}
+
+fn main() {
+    let mut pipeline = Pipeline::new();
+    pipeline.push(tag("a")).push(tag("b"));
+
+    let mut cx = Context::default();
+    assert!(block_on(pipeline.run(&mut cx)).is_ok());
+    assert_eq!(cx.trace, ["a", "b"]);
+
+    let mut cx = Context::default();
+    assert!(block_on(tag("c").call(&mut cx)).is_ok());
+    assert_eq!(cx.trace, ["c"]);
+}
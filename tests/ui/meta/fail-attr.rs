@@ -0,0 +1,9 @@
+use handle::HandlerMeta;
+
+struct Log;
+
+#[derive(HandlerMeta)]
+#[handler(around = [Log])]
+struct Auth;
+
+fn main() {}
@@ -0,0 +1,9 @@
+use handle::HandlerMeta;
+
+struct Log<T>(T);
+
+#[derive(HandlerMeta)]
+#[handler(after = [Log<T>])]
+struct Auth<T>(T);
+
+fn main() {}
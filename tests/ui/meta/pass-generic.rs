@@ -0,0 +1,13 @@
+use handle::HandlerMeta;
+
+#[derive(HandlerMeta)]
+struct Log;
+
+// The lists may name other types than the parameters.
+#[derive(HandlerMeta)]
+#[handler(after = [Log, Option<Log>])]
+struct Cache<T>(T);
+
+fn main() {
+    assert_eq!(<Cache<u8> as HandlerMeta>::must_run_after().len(), 2);
+}
@@ -0,0 +1,59 @@
+use handle::{BoxFuture, Handle, HandleExt, HandlerMeta, Pipeline};
+
+struct Context;
+
+macro_rules! handlers {
+    ($($name:ident),*) => {$(
+        impl<'a> Handle<'a, Context> for $name {
+            type Output = Option<()>;
+
+            fn call(&'a self, _: &'a mut Context) -> BoxFuture<'a, Self::Output> {
+                Box::pin(async { Some(()) })
+            }
+        }
+    )*};
+}
+
+#[derive(HandlerMeta)]
+struct Log;
+
+#[derive(HandlerMeta)]
+#[handler(before = [Route], after = [Log])]
+struct Auth;
+
+#[derive(HandlerMeta)]
+#[handler(after = [Log, Auth])]
+struct Route;
+
+handlers!(Log, Auth, Route);
+
+fn main() {
+    assert_eq!(<Auth as HandlerMeta>::name(), "Auth");
+
+    let mut pipeline = Pipeline::new();
+    pipeline
+        .push(Log.ordered())
+        .push(Auth.ordered())
+        .push(Route.ordered());
+    assert!(pipeline.validate().is_ok());
+
+    let mut pipeline = Pipeline::new();
+    pipeline
+        .push(Route.ordered())
+        .push(Auth.ordered())
+        .push(Log.ordered());
+    let violations: Vec<_> = pipeline
+        .validate()
+        .unwrap_err()
+        .iter()
+        .map(ToString::to_string)
+        .collect();
+    assert_eq!(
+        violations,
+        [
+            "`Log` must run before `Route`",
+            "`Auth` must run before `Route`",
+            "`Log` must run before `Auth`",
+        ]
+    );
+}